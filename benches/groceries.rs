@@ -0,0 +1,27 @@
+//! End-to-end mining over the bundled grocery dataset, the same data on every
+//! machine:
+//!
+//! ```sh
+//! cargo bench --bench groceries --features bench-dataset
+//! ```
+
+use apriori::itemsets::support::SupportFraction;
+use apriori::{itemsets::count::generate_frequent_itemsets, reader::load_bench_dataset};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn bench_groceries(c: &mut Criterion) {
+    let dataset = load_bench_dataset();
+
+    c.bench_function("groceries/k=3", |b| {
+        b.iter(|| {
+            let raw_transactions = dataset
+                .iter()
+                .map(|transaction| transaction.iter().map(String::as_str).collect())
+                .collect();
+            generate_frequent_itemsets(black_box(raw_transactions), SupportFraction(0.01), 3)
+        })
+    });
+}
+
+criterion_group!(benches, bench_groceries);
+criterion_main!(benches);
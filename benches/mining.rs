@@ -0,0 +1,39 @@
+//! End-to-end mining over a synthetic dataset. Compare hashers with
+//!
+//! ```sh
+//! cargo bench --bench mining
+//! cargo bench --bench mining --features fast-hash
+//! ```
+
+use apriori::itemsets::support::SupportFraction;
+use apriori::{itemsets::count::generate_frequent_itemsets_id, types::RawTransactionId};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn synthetic_transactions(n: usize, n_items: usize, len: usize) -> Vec<RawTransactionId> {
+    let mut rng = StdRng::seed_from_u64(42);
+    (0..n)
+        .map(|_| {
+            (0..len)
+                .map(|_| {
+                    // skew towards low ids so that longer itemsets become frequent
+                    let x: f64 = rng.gen();
+                    (x * x * n_items as f64) as usize
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn bench_mining(c: &mut Criterion) {
+    let transactions = synthetic_transactions(5_000, 100, 10);
+
+    c.bench_function("mining/k=3", |b| {
+        b.iter(|| {
+            generate_frequent_itemsets_id(black_box(transactions.clone()), SupportFraction(0.02), 3)
+        })
+    });
+}
+
+criterion_group!(benches, bench_mining);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+use apriori::{
+    itemsets::count::{count_pairs, count_pairs_serial},
+    types::{ItemCounts, Transaction},
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn synthetic_transactions(n: usize, n_items: usize, len: usize) -> Vec<Transaction> {
+    let mut rng = StdRng::seed_from_u64(42);
+    (0..n)
+        .map(|_| {
+            let mut transaction: Transaction =
+                (0..len).map(|_| rng.gen_range(0..n_items)).collect();
+            transaction.sort_unstable();
+            transaction.dedup();
+            transaction
+        })
+        .collect()
+}
+
+fn bench_pair_counting(c: &mut Criterion) {
+    let transactions = synthetic_transactions(20_000, 200, 15);
+    let item_counts: ItemCounts = (0..200).map(|item| (item, 1)).collect();
+
+    let mut group = c.benchmark_group("pair_counting");
+    group.bench_function("serial", |b| {
+        b.iter(|| count_pairs_serial(black_box(&transactions), black_box(&item_counts)))
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| count_pairs(black_box(&transactions), black_box(&item_counts)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_pair_counting);
+criterion_main!(benches);
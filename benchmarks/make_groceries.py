@@ -0,0 +1,52 @@
+"""Regenerate data/groceries.csv, the fixed dataset behind
+`apriori::reader::load_bench_dataset`: 3000 synthetic grocery baskets, one
+per line, with item popularities and a few bundles bought together.
+
+    python benchmarks/make_groceries.py
+"""
+import random
+
+ITEMS = {
+    "whole milk": 0.25, "bread": 0.2, "eggs": 0.15, "bananas": 0.14, "yogurt": 0.13,
+    "butter": 0.1, "cheese": 0.1, "apples": 0.1, "coffee": 0.09, "tomatoes": 0.09,
+    "onions": 0.08, "potatoes": 0.08, "chicken": 0.08, "rice": 0.07, "pasta": 0.07,
+    "orange juice": 0.07, "soda": 0.09, "water": 0.1, "cereal": 0.06, "carrots": 0.06,
+    "lettuce": 0.05, "beef": 0.05, "tea": 0.04, "sugar": 0.04, "flour": 0.03,
+    "chocolate": 0.06, "chips": 0.06, "beer": 0.05, "wine": 0.04, "ice cream": 0.04,
+    "frozen pizza": 0.04, "salmon": 0.03, "garlic": 0.04, "peppers": 0.04,
+    "cucumbers": 0.04, "toilet paper": 0.05, "detergent": 0.03, "dish soap": 0.03,
+    "shampoo": 0.02, "napkins": 0.03,
+}
+BUNDLES = [
+    (0.08, ["bread", "butter"]),
+    (0.06, ["pasta", "tomatoes", "garlic"]),
+    (0.05, ["cereal", "whole milk"]),
+    (0.04, ["beer", "chips"]),
+    (0.04, ["coffee", "whole milk", "sugar"]),
+    (0.03, ["chicken", "rice", "onions"]),
+    (0.03, ["lettuce", "tomatoes", "cucumbers"]),
+    (0.02, ["flour", "sugar", "eggs", "butter"]),
+    (0.02, ["wine", "cheese"]),
+]
+
+
+def main():
+    rng = random.Random(2021)
+    lines = []
+    for _ in range(3000):
+        basket = {item for item, p in ITEMS.items() if rng.random() < p}
+        for p, bundle in BUNDLES:
+            if rng.random() < p:
+                basket.update(bundle)
+        if not basket:
+            basket.add(rng.choice(list(ITEMS)))
+        order = sorted(basket)
+        rng.shuffle(order)
+        lines.append(",".join(order))
+
+    with open("benchmarks/data/groceries.csv", "w", encoding="utf-8") as f:
+        f.write("\n".join(lines) + "\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,76 @@
+//! Peak resident memory of the first pass under different encoding strategies.
+//! Linux only (reads `VmHWM` from `/proc/self/status`). Run each mode in its own
+//! process:
+//!
+//! ```sh
+//! cargo run --release --example peak_memory -- held
+//! cargo run --release --example peak_memory -- consumed
+//! cargo run --release --example peak_memory -- file
+//! ```
+
+use apriori::{
+    itemsets::{encode::Encoder, source::encode_to_file},
+    types::{RawTransaction, Transaction},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const N: usize = 500_000;
+const NUM_ITEMS: usize = 5_000;
+const ITEMS_PER_TRANSACTION: usize = 20;
+
+fn peak_rss_kib() -> u64 {
+    let status = std::fs::read_to_string("/proc/self/status").expect("Linux only");
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+        .expect("VmHWM in /proc/self/status")
+}
+
+fn raw_transactions(names: &[String]) -> impl Iterator<Item = RawTransaction<'_>> {
+    let mut rng = StdRng::seed_from_u64(42);
+    (0..N).map(move |_| {
+        (0..ITEMS_PER_TRANSACTION)
+            .map(|_| names[rng.gen_range(0..names.len())].as_str())
+            .collect()
+    })
+}
+
+fn main() {
+    let mode = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "consumed".to_string());
+    let names: Vec<String> = (0..NUM_ITEMS).map(|i| format!("item-{}", i)).collect();
+    let baseline = peak_rss_kib();
+
+    match mode.as_str() {
+        // raw and encoded transactions alive at the same time
+        "held" => {
+            let raw: Vec<RawTransaction> = raw_transactions(&names).collect();
+            let mut encoder = Encoder::new();
+            let encoded: Vec<Transaction> = raw.iter().map(|t| encoder.encode(t)).collect();
+            println!("encoded {} transactions", encoded.len());
+        }
+        // raw transactions freed as they are encoded
+        "consumed" => {
+            let raw: Vec<RawTransaction> = raw_transactions(&names).collect();
+            let mut encoder = Encoder::new();
+            let encoded: Vec<Transaction> = raw.into_iter().map(|t| encoder.encode(&t)).collect();
+            println!("encoded {} transactions", encoded.len());
+        }
+        // streamed from the generator straight into a file
+        "file" => {
+            let path = std::env::temp_dir().join("apriori-peak-memory.bin");
+            let (_, _, file) = encode_to_file(raw_transactions(&names), &path).unwrap();
+            println!("encoded into {}", file.path().display());
+            std::fs::remove_file(&path).unwrap();
+        }
+        other => panic!("unknown mode {:?}", other),
+    }
+
+    println!(
+        "{}: peak RSS grew by {} MiB",
+        mode,
+        (peak_rss_kib() - baseline) / 1024
+    );
+}
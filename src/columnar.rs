@@ -0,0 +1,218 @@
+#[cfg(feature = "arrow")]
+use arrow_array::{Array, BooleanArray, RecordBatch};
+#[cfg(feature = "arrow")]
+use arrow_schema::ArrowError;
+use bitvec::prelude::*;
+
+use crate::types::{Inventory, ItemName, Transaction};
+
+/// Encode a one-hot frame, one boolean column per item and one row per
+/// transaction, into transactions and the inventory of the column names.
+///
+/// Column `i` becomes item id `i`, and each transaction holds, in ascending
+/// order, the ids of the columns whose bit is set in its row. Only set bits
+/// are visited, column by column, and no row is ever laid out densely.
+///
+/// Arrow, and hence Polars, stores boolean columns as least-significant-bit
+/// first byte bitmaps, so a column's values buffer can be passed as a
+/// `BitSlice<Lsb0, u8>` from [`BitSlice::from_slice`], cut to the column's
+/// length, without copying. Null bits must already be cleared.
+///
+/// # Panics
+///
+/// If the columns are not all the same length.
+pub fn transactions_from_bit_columns<'l, O, T>(
+    columns: &[(ItemName<'l>, &BitSlice<O, T>)],
+) -> (Vec<Transaction>, Inventory<'l>)
+where
+    O: BitOrder,
+    T: BitStore,
+{
+    let num_rows = columns.first().map_or(0, |(_, column)| column.len());
+    let mut transactions: Vec<Transaction> = vec![vec![]; num_rows];
+    let mut inventory: Inventory<'l> =
+        Inventory::with_capacity_and_hasher(columns.len(), Default::default());
+
+    for (item_id, &(name, column)) in columns.iter().enumerate() {
+        assert_eq!(
+            column.len(),
+            num_rows,
+            "column {:?} has {} rows, expected {}",
+            name,
+            column.len(),
+            num_rows
+        );
+
+        inventory.insert(item_id, name);
+        for row in column.iter_ones() {
+            transactions[row].push(item_id);
+        }
+    }
+
+    (transactions, inventory)
+}
+
+/// [`transactions_from_bit_columns`] over Arrow boolean arrays, one per item.
+///
+/// Each array's values bitmap is read in place, offset and all. Nulls count
+/// as absent: an array with nulls has its values masked by its validity
+/// bitmap first, which copies that one column.
+///
+/// # Panics
+///
+/// If the arrays are not all the same length.
+#[cfg(feature = "arrow")]
+pub fn transactions_from_boolean_arrays<'l>(
+    columns: &[(ItemName<'l>, &BooleanArray)],
+) -> (Vec<Transaction>, Inventory<'l>) {
+    let masked: Vec<_> = columns
+        .iter()
+        .map(|(_, array)| array.nulls().map(|nulls| array.values() & nulls.inner()))
+        .collect();
+    let bit_columns: Vec<(ItemName<'l>, &BitSlice<Lsb0, u8>)> = columns
+        .iter()
+        .zip(&masked)
+        .map(|(&(name, array), masked)| {
+            let buffer = masked.as_ref().unwrap_or_else(|| array.values());
+            let bits = BitSlice::from_slice(buffer.values()).expect("bitmap is addressable");
+            (name, &bits[buffer.offset()..][..buffer.len()])
+        })
+        .collect();
+
+    transactions_from_bit_columns(&bit_columns)
+}
+
+/// [`transactions_from_boolean_arrays`] over every column of `batch`, each
+/// item named after its field.
+///
+/// Fails with [`ArrowError::InvalidArgumentError`] naming the first column
+/// that is not boolean.
+#[cfg(feature = "arrow")]
+pub fn transactions_from_record_batch(
+    batch: &RecordBatch,
+) -> Result<(Vec<Transaction>, Inventory<'_>), ArrowError> {
+    let columns = batch
+        .schema_ref()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, column)| {
+            let array = column
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .ok_or_else(|| {
+                    ArrowError::InvalidArgumentError(format!(
+                        "column {:?} is {}, not boolean",
+                        field.name(),
+                        field.data_type()
+                    ))
+                })?;
+            Ok((field.name().as_str(), array))
+        })
+        .collect::<Result<Vec<_>, ArrowError>>()?;
+
+    Ok(transactions_from_boolean_arrays(&columns))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::{
+        count::try_generate_frequent_itemsets_id, options::MiningOptions, support::SupportFraction,
+    };
+    use crate::types::RawTransactionId;
+
+    #[test]
+    fn test_transactions_from_bit_columns() {
+        // rows: {bread, milk}, {bread}, {bread, milk, jam}, {milk}, {}
+        // the column bits as Arrow values buffers hold them
+        let buffers = [[0b0000_0111_u8], [0b0000_1101], [0b0000_0100]];
+        let column = |i: usize| &BitSlice::<Lsb0, u8>::from_slice(&buffers[i]).unwrap()[..5];
+        let columns = [
+            ("bread", column(0)),
+            ("milk", column(1)),
+            ("jam", column(2)),
+        ];
+        let (transactions, inventory) = transactions_from_bit_columns(&columns);
+
+        assert_eq!(
+            transactions,
+            vec![vec![0, 1], vec![0], vec![0, 1, 2], vec![1], vec![]]
+        );
+        assert_eq!(
+            inventory,
+            hashmap! { 0 => "bread", 1 => "milk", 2 => "jam" }
+        );
+
+        let raw_transactions: Vec<RawTransactionId> = transactions
+            .into_iter()
+            .map(|transaction| transaction.into_iter().collect())
+            .collect();
+        let (itemsets, _) = try_generate_frequent_itemsets_id(
+            raw_transactions,
+            SupportFraction(0.4),
+            Some(2),
+            &MiningOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(itemsets[&1], hashmap! { itemset![0] => 3, itemset![1] => 3 });
+        assert_eq!(itemsets[&2], hashmap! { itemset![0, 1] => 2 });
+    }
+
+    #[test]
+    #[should_panic(expected = "column \"milk\" has 2 rows, expected 3")]
+    fn test_transactions_from_bit_columns_uneven() {
+        let bread = bitvec![1, 0, 1];
+        let milk = bitvec![1, 1];
+        transactions_from_bit_columns(&[("bread", &bread[..]), ("milk", &milk[..])]);
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_transactions_from_boolean_arrays() {
+        // rows: {bread, milk}, {bread}, {bread, milk, jam}, {milk}, {}
+        let bread = BooleanArray::from(vec![true, true, true, false, false]);
+        // a null is as good as absent
+        let milk = BooleanArray::from(vec![Some(true), None, Some(true), Some(true), None]);
+        // sliced out of a longer array, so its bits start at an offset
+        let jam = BooleanArray::from(vec![true, false, false, true, false, false]).slice(1, 5);
+
+        let (transactions, inventory) =
+            transactions_from_boolean_arrays(&[("bread", &bread), ("milk", &milk), ("jam", &jam)]);
+
+        assert_eq!(
+            transactions,
+            vec![vec![0, 1], vec![0], vec![0, 1, 2], vec![1], vec![]]
+        );
+        assert_eq!(
+            inventory,
+            hashmap! { 0 => "bread", 1 => "milk", 2 => "jam" }
+        );
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_transactions_from_record_batch() {
+        use arrow_array::{ArrayRef, Int32Array};
+        use std::sync::Arc;
+
+        let bread: ArrayRef = Arc::new(BooleanArray::from(vec![true, true, false]));
+        let milk: ArrayRef = Arc::new(BooleanArray::from(vec![true, false, true]));
+        let batch =
+            RecordBatch::try_from_iter(vec![("bread", bread.clone()), ("milk", milk)]).unwrap();
+
+        let (transactions, inventory) = transactions_from_record_batch(&batch).unwrap();
+        assert_eq!(transactions, vec![vec![0, 1], vec![0], vec![1]]);
+        assert_eq!(inventory, hashmap! { 0 => "bread", 1 => "milk" });
+
+        let counts: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3]));
+        let batch = RecordBatch::try_from_iter(vec![("bread", bread), ("count", counts)]).unwrap();
+        let err = transactions_from_record_batch(&batch).unwrap_err();
+        assert!(
+            err.to_string().contains("column \"count\" is Int32"),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use crate::types::ItemsetLength;
+
+/// Reasons a mining run can stop before producing a result.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AprioriError {
+    /// Generating the candidates for `level` would exceed the configured budget.
+    CandidateBudgetExceeded {
+        level: ItemsetLength,
+        estimate: usize,
+        budget: usize,
+    },
+    /// The support threshold needs `required` transactions but only `n` exist,
+    /// so nothing can be frequent.
+    ThresholdUnreachable { required: usize, n: usize },
+    /// A threshold of `required` transactions, zero, cannot prune anything,
+    /// and neither a maximum itemset length nor a candidate budget was given
+    /// to bound the enumeration.
+    ThresholdTooLow { required: usize },
+    /// The run's cancel flag was set.
+    Cancelled,
+    /// Reading the transactions from a
+    /// [`TransactionSource`](crate::itemsets::source::TransactionSource) failed.
+    /// Holds the parts of the [`std::io::Error`], which is neither `Clone` nor
+    /// `PartialEq`.
+    Io {
+        kind: std::io::ErrorKind,
+        message: String,
+    },
+}
+
+impl fmt::Display for AprioriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AprioriError::CandidateBudgetExceeded {
+                level,
+                estimate,
+                budget,
+            } => write!(
+                f,
+                "level {} would generate up to {} candidates, over the budget of {}",
+                level, estimate, budget
+            ),
+            AprioriError::ThresholdUnreachable { required, n } => write!(
+                f,
+                "support threshold needs {} transactions but only {} were given",
+                required, n
+            ),
+            AprioriError::ThresholdTooLow { required } => write!(
+                f,
+                "a support threshold of {} transactions prunes nothing; \
+                 set a maximum itemset length or a candidate budget",
+                required
+            ),
+            AprioriError::Cancelled => write!(f, "mining was cancelled"),
+            AprioriError::Io { message, .. } => {
+                write!(f, "reading the transactions failed: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AprioriError {}
+
+impl From<std::io::Error> for AprioriError {
+    fn from(err: std::io::Error) -> Self {
+        AprioriError::Io {
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
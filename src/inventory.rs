@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use crate::types::{
+    Inventory, ItemId, ItemName, Itemset, ItemsetCounts, RemapTable, ReverseLookup,
+};
+
+/// Merge the inventories of two separately mined shards into one id space.
+///
+/// Items of `a` keep their ids. Items only in `b` get fresh ids after the
+/// largest id of `a`. The returned table maps every id of `b` to its merged id,
+/// for use with [`remap_itemset_counts`].
+pub fn merge_inventories<'l>(a: &Inventory<'l>, b: &Inventory<'l>) -> (Inventory<'l>, RemapTable) {
+    let mut merged = a.clone();
+    let mut lookup: ReverseLookup = a.iter().map(|(&id, &name)| (name, id)).collect();
+    let mut next_item_id = a.keys().max().map_or(0, |max_id| max_id + 1);
+    let mut remap: RemapTable = HashMap::with_capacity_and_hasher(b.len(), Default::default());
+
+    // assign in id order so the result does not depend on hashing
+    let mut b_items: Vec<_> = b.iter().collect();
+    b_items.sort_unstable();
+
+    for (&b_id, &name) in b_items {
+        let merged_id = *lookup.entry(name).or_insert_with(|| {
+            let item_id = next_item_id;
+            next_item_id += 1;
+            merged.insert(item_id, name);
+            item_id
+        });
+        remap.insert(b_id, merged_id);
+    }
+
+    (merged, remap)
+}
+
+/// Rewrite the item ids of `itemset_counts` through `remap`.
+/// Itemsets are re-sorted to keep the stored-key convention.
+///
+/// Panics if an item id is missing from `remap`.
+pub fn remap_itemset_counts(itemset_counts: &ItemsetCounts, remap: &RemapTable) -> ItemsetCounts {
+    itemset_counts
+        .iter()
+        .map(|(itemset, &count)| {
+            let mut remapped: Itemset = itemset.iter().map(|item_id| remap[item_id]).collect();
+            remapped.sort_unstable();
+            (remapped, count)
+        })
+        .collect()
+}
+
+/// Names of the items of `itemset`, in the same order.
+///
+/// Panics if an item id is missing from `inventory`.
+pub fn itemset_to_names<'l>(itemset: &[ItemId], inventory: &Inventory<'l>) -> Vec<ItemName<'l>> {
+    itemset.iter().map(|item_id| inventory[item_id]).collect()
+}
+
+/// The itemset made of `names`, sorted as stored keys are, e.g. for looking
+/// it up in [`FrequentItemsets`](crate::types::FrequentItemsets).
+/// Returns `None` if any name is missing from `reverse_lookup`.
+pub fn names_to_itemset<S: AsRef<str>>(
+    names: &[S],
+    reverse_lookup: &ReverseLookup,
+) -> Option<Itemset> {
+    let mut itemset = names
+        .iter()
+        .map(|name| reverse_lookup.get(name.as_ref()).copied())
+        .collect::<Option<Itemset>>()?;
+    itemset.sort_unstable();
+    Some(itemset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_inventories() {
+        let a: Inventory = hashmap! { 0 => "bread", 1 => "milk" };
+        let b: Inventory = hashmap! { 0 => "milk", 1 => "cheese", 2 => "bread" };
+
+        let (merged, remap) = merge_inventories(&a, &b);
+
+        assert_eq!(
+            merged,
+            hashmap! { 0 => "bread", 1 => "milk", 2 => "cheese" }
+        );
+        assert_eq!(remap, hashmap! { 0 => 1, 1 => 2, 2 => 0 });
+    }
+
+    #[test]
+    fn test_merge_empty_inventory() {
+        let a = Inventory::default();
+        let b: Inventory = hashmap! { 0 => "milk", 1 => "cheese" };
+
+        let (merged, remap) = merge_inventories(&a, &b);
+
+        assert_eq!(merged, b);
+        assert_eq!(remap, hashmap! { 0 => 0, 1 => 1 });
+    }
+
+    #[test]
+    fn test_remap_itemset_counts() {
+        let remap: RemapTable = hashmap! { 0 => 1, 1 => 2, 2 => 0 };
+        let itemset_counts: ItemsetCounts = hashmap! {
+            itemset![0] => 4,
+            itemset![0, 1] => 3,
+            itemset![1, 2] => 2,
+        };
+
+        let remapped = remap_itemset_counts(&itemset_counts, &remap);
+
+        assert_eq!(
+            remapped,
+            hashmap! {
+                itemset![1] => 4,
+                itemset![1, 2] => 3,
+                itemset![0, 2] => 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_itemset_names_round_trip() {
+        let inventory: Inventory = hashmap! { 0 => "bread", 1 => "milk", 2 => "cheese" };
+        let reverse_lookup: ReverseLookup = hashmap! { "bread" => 0, "milk" => 1, "cheese" => 2 };
+
+        let names = itemset_to_names(&[0, 2], &inventory);
+        assert_eq!(names, vec!["bread", "cheese"]);
+        assert_eq!(names_to_itemset(&names, &reverse_lookup), Some(itemset![0, 2]));
+
+        // owned names in any order come back sorted by id
+        let names = vec![
+            "cheese".to_string(),
+            "milk".to_string(),
+            "bread".to_string(),
+        ];
+        let itemset = names_to_itemset(&names, &reverse_lookup).unwrap();
+        assert_eq!(itemset, itemset![0, 1, 2]);
+        assert_eq!(
+            itemset_to_names(&itemset, &inventory),
+            vec!["bread", "milk", "cheese"]
+        );
+
+        assert_eq!(
+            names_to_itemset(&["bread", "caviar"], &reverse_lookup),
+            None
+        );
+    }
+}
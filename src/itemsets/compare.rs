@@ -0,0 +1,334 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    inventory::names_to_itemset,
+    result::MiningResult,
+    types::{FrequentItemsets, HashState, ItemName, Itemset, ItemsetCounts, ItemsetLength},
+};
+
+/// Per-level differences between two mining results.
+/// Levels without any difference are left out.
+#[derive(Debug, Default, PartialEq)]
+pub struct ItemsetDiff {
+    /// Frequent in `b` but not in `a`, with their counts in `b`.
+    pub added: FrequentItemsets,
+    /// Frequent in `a` but not in `b`, with their counts in `a`.
+    pub removed: FrequentItemsets,
+    /// Frequent in both with different counts, as `(count in a, count in b)`.
+    pub changed: HashMap<ItemsetLength, HashMap<Itemset, (u32, u32), HashState>, HashState>,
+}
+
+/// Compare two mining results level by level.
+/// Both results must share the same item id space.
+pub fn diff_itemsets(a: &FrequentItemsets, b: &FrequentItemsets) -> ItemsetDiff {
+    let mut diff = ItemsetDiff::default();
+
+    for (&size, a_counts) in a {
+        for (itemset, &a_count) in a_counts {
+            match b.get(&size).and_then(|b_counts| b_counts.get(itemset)) {
+                None => {
+                    diff.removed
+                        .entry(size)
+                        .or_default()
+                        .insert(itemset.clone(), a_count);
+                }
+                Some(&b_count) if b_count != a_count => {
+                    diff.changed
+                        .entry(size)
+                        .or_default()
+                        .insert(itemset.clone(), (a_count, b_count));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    for (&size, b_counts) in b {
+        for (itemset, &b_count) in b_counts {
+            let in_a = a
+                .get(&size)
+                .is_some_and(|a_counts| a_counts.contains_key(itemset));
+            if !in_a {
+                diff.added
+                    .entry(size)
+                    .or_default()
+                    .insert(itemset.clone(), b_count);
+            }
+        }
+    }
+
+    diff
+}
+
+/// Itemsets frequent in `current` with substantially lower support in
+/// `baseline`, with their counts in `current`. Levels without any such itemset
+/// are left out.
+///
+/// An itemset is novel when it is not frequent in `baseline` at all, or when
+/// its support in `current` is at least `min_ratio` times its support in
+/// `baseline`. Support is compared as a fraction of each side's `N`, so
+/// windows of different sizes compare fairly. With `min_ratio` of
+/// `f32::INFINITY`, only the itemsets missing from `baseline` are novel.
+///
+/// Both results must share the same item id space, e.g. by mining them with the
+/// same seeded inventory. To also catch itemsets whose support dropped,
+/// compare counts through [`diff_itemsets`] instead.
+pub fn novel_itemsets(
+    current: &FrequentItemsets,
+    current_N: usize,
+    baseline: &FrequentItemsets,
+    baseline_N: usize,
+    min_ratio: f32,
+) -> FrequentItemsets {
+    let mut novel = FrequentItemsets::default();
+    // count / current_N >= min_ratio * baseline_count / baseline_N, without
+    // dividing by either N
+    let is_novel = |count: u32, baseline_count: Option<&u32>| match baseline_count {
+        None => true,
+        Some(&baseline_count) => {
+            count as f64 * baseline_N as f64
+                >= f64::from(min_ratio) * baseline_count as f64 * current_N as f64
+        }
+    };
+
+    for (&size, counts) in current {
+        let baseline_counts = baseline.get(&size);
+        let novel_counts: ItemsetCounts = counts
+            .iter()
+            .filter(|&(itemset, &count)| {
+                is_novel(count, baseline_counts.and_then(|b| b.get(itemset)))
+            })
+            .map(|(itemset, &count)| (itemset.clone(), count))
+            .collect();
+        if !novel_counts.is_empty() {
+            novel.insert(size, novel_counts);
+        }
+    }
+
+    novel
+}
+
+/// A rolling series of mining results, one per time window, oldest first, for
+/// following how the support of itemsets evolves.
+///
+/// Each window keeps its own inventory, so windows mined independently, with
+/// different item ids, can be compared by item name.
+#[derive(Debug, Default)]
+pub struct TrendTracker<'l> {
+    windows: VecDeque<MiningResult<'l>>,
+    max_windows: Option<usize>,
+}
+
+impl<'l> TrendTracker<'l> {
+    /// A tracker keeping every window pushed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A tracker keeping only the latest `max_windows` windows.
+    pub fn with_max_windows(max_windows: usize) -> Self {
+        Self {
+            windows: VecDeque::with_capacity(max_windows + 1),
+            max_windows: Some(max_windows),
+        }
+    }
+
+    /// Append the result for the next window, dropping the oldest one if
+    /// that makes more than `max_windows`.
+    pub fn push(&mut self, window: MiningResult<'l>) {
+        self.windows.push_back(window);
+        if let Some(max_windows) = self.max_windows {
+            while self.windows.len() > max_windows {
+                self.windows.pop_front();
+            }
+        }
+    }
+
+    /// The windows held, oldest first.
+    pub fn windows(&self) -> impl Iterator<Item = &MiningResult<'l>> {
+        self.windows.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Support count of the itemset made of `itemset` in each window, oldest
+    /// first, or `None` in the windows where it is not frequent.
+    pub fn trajectory(&self, itemset: &[ItemName]) -> Vec<Option<u32>> {
+        self.windows
+            .iter()
+            .map(|window| {
+                let ids = names_to_itemset(itemset, &window.reverse_lookup)?;
+                window.itemsets.get(&ids.len())?.get(&ids).copied()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::support::SupportFraction;
+
+    fn fixture() -> FrequentItemsets {
+        hashmap! {
+            1 => hashmap! {
+                itemset![0] => 3,
+                itemset![1] => 3,
+                itemset![2] => 2,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 2,
+                itemset![0, 2] => 2,
+            },
+        }
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        assert_eq!(diff_itemsets(&fixture(), &fixture()), ItemsetDiff::default());
+    }
+
+    #[test]
+    fn test_diff_added_removed() {
+        let a = fixture();
+        let mut b = fixture();
+        let level_2 = b.get_mut(&2).unwrap();
+        level_2.remove(&itemset![0, 2]);
+        level_2.insert(itemset![1, 2], 2);
+
+        let diff = diff_itemsets(&a, &b);
+
+        assert_eq!(diff.added, hashmap! { 2 => hashmap! { itemset![1, 2] => 2 } });
+        assert_eq!(diff.removed, hashmap! { 2 => hashmap! { itemset![0, 2] => 2 } });
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_changed() {
+        let a = fixture();
+        let mut b = fixture();
+        b.get_mut(&1).unwrap().insert(itemset![2], 5);
+
+        let diff = diff_itemsets(&a, &b);
+
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed, hashmap! { 1 => hashmap! { itemset![2] => (2, 5) } });
+    }
+
+    #[test]
+    fn test_diff_missing_level() {
+        let a = fixture();
+        let mut b = fixture();
+        b.insert(3, hashmap! { itemset![0, 1, 2] => 1 });
+
+        let diff = diff_itemsets(&a, &b);
+
+        assert_eq!(diff.added, hashmap! { 3 => hashmap! { itemset![0, 1, 2] => 1 } });
+    }
+
+    #[test]
+    fn test_novel_itemsets() {
+        let baseline = fixture();
+        let mut current = fixture();
+        current.get_mut(&2).unwrap().insert(itemset![1, 2], 4);
+        current.insert(3, hashmap! { itemset![0, 1, 2] => 2 });
+        // a changed count alone is not novel
+        current.get_mut(&1).unwrap().insert(itemset![2], 5);
+
+        let novel = novel_itemsets(&current, 4, &baseline, 4, f32::INFINITY);
+
+        assert_eq!(
+            novel,
+            hashmap! {
+                2 => hashmap! { itemset![1, 2] => 4 },
+                3 => hashmap! { itemset![0, 1, 2] => 2 },
+            }
+        );
+        assert!(novel_itemsets(&baseline, 4, &baseline, 4, f32::INFINITY).is_empty());
+    }
+
+    #[test]
+    fn test_novel_itemsets_min_ratio() {
+        // mined from 4 transactions
+        let baseline = fixture();
+        // mined from 8: every count doubles, but only [2] gains support
+        let current = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 6,
+                itemset![1] => 6,
+                itemset![2] => 8,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 4,
+                itemset![0, 2] => 4,
+                itemset![1, 2] => 4,
+            },
+        };
+
+        // 8/8 against 2/4
+        assert_eq!(
+            novel_itemsets(&current, 8, &baseline, 4, 1.5),
+            hashmap! {
+                1 => hashmap! { itemset![2] => 8 },
+                2 => hashmap! { itemset![1, 2] => 4 },
+            }
+        );
+        assert_eq!(
+            novel_itemsets(&current, 8, &baseline, 4, 2.5),
+            hashmap! { 2 => hashmap! { itemset![1, 2] => 4 } }
+        );
+        // at a ratio of 1, equal support is enough
+        assert_eq!(novel_itemsets(&current, 8, &baseline, 4, 1.0)[&1].len(), 3);
+    }
+
+    #[test]
+    fn test_trend_tracker_trajectory() {
+        let window = |transactions: Vec<Vec<&'static str>>| {
+            let raw_transactions = transactions
+                .into_iter()
+                .map(|items| items.into_iter().collect())
+                .collect();
+            MiningResult::mine(raw_transactions, SupportFraction(0.5), 2)
+        };
+        let mut tracker = TrendTracker::new();
+        tracker.push(window(vec![
+            vec!["bread", "milk"],
+            vec!["bread", "jam"],
+            vec!["bread"],
+            vec!["milk", "jam"],
+        ]));
+        tracker.push(window(vec![
+            vec!["bread", "milk"],
+            vec!["milk", "bread", "jam"],
+            vec!["bread", "milk"],
+            vec!["jam"],
+        ]));
+
+        assert_eq!(tracker.trajectory(&["milk", "bread"]), vec![None, Some(3)]);
+        assert_eq!(tracker.trajectory(&["bread"]), vec![Some(3), Some(3)]);
+        assert_eq!(tracker.trajectory(&["tea"]), vec![None, None]);
+    }
+
+    #[test]
+    fn test_trend_tracker_max_windows() {
+        let mut tracker = TrendTracker::with_max_windows(2);
+        for n in 1..=3 {
+            let raw_transactions = vec![vec!["bread"].into_iter().collect(); n];
+            tracker.push(MiningResult::mine(
+                raw_transactions,
+                SupportFraction(0.5),
+                1,
+            ));
+        }
+
+        assert_eq!(tracker.len(), 2);
+        assert_eq!(tracker.trajectory(&["bread"]), vec![Some(2), Some(3)]);
+    }
+}
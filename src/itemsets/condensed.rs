@@ -0,0 +1,442 @@
+use std::collections::HashSet;
+
+use crate::{
+    itemsets::{count::count_frequent_items, options::RoundingMode, support::SupportFraction},
+    types::{FrequentItemsets, Inventory, ItemId, Itemset, RawTransaction},
+};
+
+/// The maximal frequent itemsets, i.e. those with no frequent proper superset.
+///
+/// An itemset is kept if no itemset one item longer contains it, which is
+/// enough by downward closure as long as every level was mined. If mining
+/// stopped at some `k`, the result is maximal among itemsets of up to `k`
+/// items. Ordered by length, then id.
+pub fn filter_maximal(itemsets: &FrequentItemsets) -> Vec<(Itemset, u32)> {
+    let mut maximal: Vec<(Itemset, u32)> = vec![];
+
+    for (&size, itemset_counts) in itemsets {
+        let covered: HashSet<Itemset> = itemsets
+            .get(&(size + 1))
+            .into_iter()
+            .flat_map(|supersets| supersets.keys())
+            .flat_map(|superset| {
+                (0..superset.len()).map(move |skip| {
+                    let mut subset = superset.clone();
+                    subset.remove(skip);
+                    subset
+                })
+            })
+            .collect();
+
+        maximal.extend(
+            itemset_counts
+                .iter()
+                .filter(|(itemset, _)| !covered.contains(*itemset))
+                .map(|(itemset, &count)| (itemset.clone(), count)),
+        );
+    }
+
+    sort_itemsets(&mut maximal);
+    maximal
+}
+
+/// The closed frequent itemsets, i.e. those with no proper superset of the
+/// same support.
+///
+/// Closed itemsets lose nothing: the support of any frequent itemset is that
+/// of its smallest closed superset. An itemset is kept if no itemset one item
+/// longer contains it with the same count, which is enough since support can
+/// only fall as items are added. As with [`filter_maximal`], an itemset at
+/// the last mined level is always kept. Ordered by length, then id.
+pub fn filter_closed(itemsets: &FrequentItemsets) -> Vec<(Itemset, u32)> {
+    let mut closed: Vec<(Itemset, u32)> = vec![];
+
+    for (&size, itemset_counts) in itemsets {
+        let absorbed: HashSet<Itemset> = itemsets
+            .get(&(size + 1))
+            .into_iter()
+            .flat_map(|supersets| supersets.iter())
+            .flat_map(|(superset, &count)| {
+                (0..superset.len()).filter_map(move |skip| {
+                    let mut subset = superset.clone();
+                    subset.remove(skip);
+                    (itemset_counts.get(&subset) == Some(&count)).then_some(subset)
+                })
+            })
+            .collect();
+
+        closed.extend(
+            itemset_counts
+                .iter()
+                .filter(|(itemset, _)| !absorbed.contains(*itemset))
+                .map(|(itemset, &count)| (itemset.clone(), count)),
+        );
+    }
+
+    sort_itemsets(&mut closed);
+    closed
+}
+
+/// The minimal generators (free itemsets), i.e. those whose every immediate
+/// subset has strictly greater support.
+///
+/// Dual to the closed itemsets: each closed itemset's support is shared by a
+/// set of itemsets, and its minimal generators are the smallest of them, the
+/// antecedents of an exact rule basis. Subsets are looked up one level down,
+/// which is enough since support can only fall as items are added. Every
+/// 1-itemset is kept, as the support of the empty set, N, is not known here;
+/// strictly, an item present in every transaction is not a generator. Levels
+/// are kept as they are in `itemsets`, possibly emptied.
+pub fn minimal_generators(itemsets: &FrequentItemsets) -> FrequentItemsets {
+    itemsets
+        .iter()
+        .map(|(&size, itemset_counts)| {
+            let subsets = match size.checked_sub(1).and_then(|below| itemsets.get(&below)) {
+                Some(subsets) if size > 1 => subsets,
+                _ => return (size, itemset_counts.clone()),
+            };
+            let generators = itemset_counts
+                .iter()
+                .filter(|(itemset, &count)| {
+                    (0..itemset.len()).all(|skip| {
+                        let mut subset = itemset.to_vec();
+                        subset.remove(skip);
+                        subsets
+                            .get(subset.as_slice())
+                            .is_none_or(|&subset_count| subset_count > count)
+                    })
+                })
+                .map(|(itemset, &count)| (itemset.clone(), count))
+                .collect();
+            (size, generators)
+        })
+        .collect()
+}
+
+/// Mine the maximal frequent itemsets directly, without enumerating their
+/// frequent subsets.
+///
+/// Runs a depth-first search over vertical transaction id lists, in the style
+/// of GenMax: items are tried in ascending support order, a branch whose items
+/// all fit inside an already found maximal itemset is pruned, and a branch
+/// whose head and entire tail are frequent together is taken whole without
+/// being expanded (lookahead). On dense data with long patterns this visits far
+/// fewer itemsets than level-wise mining followed by [`filter_maximal`], and it
+/// has no length limit. Ordered by length, then id.
+pub fn generate_maximal_itemsets(
+    raw_transactions: Vec<RawTransaction<'_>>,
+    min_support: SupportFraction,
+) -> (Vec<(Itemset, u32)>, Inventory<'_>) {
+    let min_support_count =
+        min_support.support_count(raw_transactions.len(), RoundingMode::default());
+    let (item_counts, inventory, transactions) =
+        count_frequent_items(raw_transactions, min_support_count);
+    let min_support_count = min_support_count.max(1);
+
+    let mut tail: Vec<Branch> = item_counts
+        .keys()
+        .map(|&item| Branch { item, tids: vec![] })
+        .collect();
+    tail.sort_unstable_by_key(|branch| (item_counts[&branch.item], branch.item));
+    let position: Vec<Option<usize>> = {
+        let max_item = tail.iter().map(|branch| branch.item + 1).max().unwrap_or(0);
+        let mut position = vec![None; max_item];
+        for (i, branch) in tail.iter().enumerate() {
+            position[branch.item] = Some(i);
+        }
+        position
+    };
+    for (tid, transaction) in transactions.iter().enumerate() {
+        for &item in transaction {
+            if let Some(Some(i)) = position.get(item) {
+                tail[*i].tids.push(tid);
+            }
+        }
+    }
+
+    let mut maximal = vec![];
+    extend(&mut Itemset::new(), &tail, min_support_count, &mut maximal);
+
+    sort_itemsets(&mut maximal);
+    (maximal, inventory)
+}
+
+/// An item that can still extend the current head, with the ids of the
+/// transactions containing the head and that item.
+struct Branch {
+    item: ItemId,
+    tids: Vec<usize>,
+}
+
+fn extend(
+    head: &mut Itemset,
+    tail: &[Branch],
+    min_support_count: usize,
+    maximal: &mut Vec<(Itemset, u32)>,
+) {
+    if tail.is_empty() {
+        return;
+    }
+    let mut everything: Itemset = head
+        .iter()
+        .copied()
+        .chain(tail.iter().map(|branch| branch.item))
+        .collect();
+    everything.sort_unstable();
+
+    // superset pruning: nothing under this node can be maximal
+    if maximal
+        .iter()
+        .any(|(itemset, _)| is_subset(&everything, itemset))
+    {
+        return;
+    }
+
+    // lookahead: the head with its whole tail is the largest itemset here
+    let mut tids = tail[0].tids.clone();
+    for branch in &tail[1..] {
+        if tids.len() < min_support_count {
+            break;
+        }
+        tids = intersect(&tids, &branch.tids);
+    }
+    if tids.len() >= min_support_count {
+        record(everything, tids.len() as u32, maximal);
+        return;
+    }
+
+    for (i, branch) in tail.iter().enumerate() {
+        let next_tail: Vec<Branch> = tail[i + 1..]
+            .iter()
+            .map(|other| Branch {
+                item: other.item,
+                tids: intersect(&branch.tids, &other.tids),
+            })
+            .filter(|other| other.tids.len() >= min_support_count)
+            .collect();
+
+        head.push(branch.item);
+        if next_tail.is_empty() {
+            let mut itemset = head.clone();
+            itemset.sort_unstable();
+            if !maximal.iter().any(|(found, _)| is_subset(&itemset, found)) {
+                record(itemset, branch.tids.len() as u32, maximal);
+            }
+        } else {
+            extend(head, &next_tail, min_support_count, maximal);
+        }
+        head.pop();
+    }
+}
+
+/// Add `itemset`, dropping anything found earlier that it contains.
+fn record(itemset: Itemset, count: u32, maximal: &mut Vec<(Itemset, u32)>) {
+    maximal.retain(|(found, _)| !is_subset(found, &itemset));
+    maximal.push((itemset, count));
+}
+
+/// Whether sorted `a` is contained in sorted `b`.
+fn is_subset(a: &[ItemId], b: &[ItemId]) -> bool {
+    a.len() <= b.len() && a.iter().all(|item| b.binary_search(item).is_ok())
+}
+
+fn intersect(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut both = Vec::with_capacity(a.len().min(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                both.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    both
+}
+
+fn sort_itemsets(itemsets: &mut [(Itemset, u32)]) {
+    itemsets.sort_unstable_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::{
+        count::try_generate_frequent_itemsets, options::MiningOptions, support::SupportFraction,
+    };
+
+    fn to_raw(transactions: Vec<Vec<&str>>) -> Vec<RawTransaction<'_>> {
+        transactions
+            .into_iter()
+            .map(|items| items.into_iter().collect())
+            .collect()
+    }
+
+    fn named(itemsets: Vec<(Itemset, u32)>, inventory: &Inventory) -> Vec<(Vec<String>, u32)> {
+        let mut named: Vec<(Vec<String>, u32)> = itemsets
+            .into_iter()
+            .map(|(itemset, count)| {
+                let mut names: Vec<String> =
+                    itemset.iter().map(|id| inventory[id].to_string()).collect();
+                names.sort_unstable();
+                (names, count)
+            })
+            .collect();
+        named.sort();
+        named
+    }
+
+    fn assert_matches_filter(raw_transactions: Vec<RawTransaction>, min_support: SupportFraction) {
+        let (maximal, inventory) = generate_maximal_itemsets(raw_transactions.clone(), min_support);
+        let (itemsets, full_inventory, _) = try_generate_frequent_itemsets(
+            raw_transactions,
+            min_support,
+            None,
+            &MiningOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            named(maximal, &inventory),
+            named(filter_maximal(&itemsets), &full_inventory)
+        );
+    }
+
+    #[test]
+    fn test_filter_maximal() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 4,
+                itemset![1] => 3,
+                itemset![2] => 3,
+                itemset![3] => 2,
+            },
+            2 => hashmap! { itemset![0, 1] => 3, itemset![0, 2] => 2, itemset![1, 2] => 2 },
+            3 => hashmap! { itemset![0, 1, 2] => 2 },
+        };
+
+        assert_eq!(
+            filter_maximal(&itemsets),
+            vec![(itemset![3], 2), (itemset![0, 1, 2], 2)]
+        );
+    }
+
+    #[test]
+    fn test_filter_closed() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 4,
+                itemset![1] => 3,
+                itemset![2] => 3,
+                itemset![3] => 2,
+            },
+            2 => hashmap! { itemset![0, 1] => 3, itemset![0, 2] => 2, itemset![1, 2] => 2 },
+            3 => hashmap! { itemset![0, 1, 2] => 2 },
+        };
+
+        // {1} and {0, 1} have the same support, as do {0, 2}, {1, 2} and
+        // {0, 1, 2}
+        assert_eq!(
+            filter_closed(&itemsets),
+            vec![
+                (itemset![0], 4),
+                (itemset![2], 3),
+                (itemset![3], 2),
+                (itemset![0, 1], 3),
+                (itemset![0, 1, 2], 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_minimal_generators() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 4,
+                itemset![1] => 3,
+                itemset![2] => 3,
+                itemset![3] => 2,
+            },
+            2 => hashmap! { itemset![0, 1] => 3, itemset![0, 2] => 2, itemset![1, 2] => 2 },
+            3 => hashmap! { itemset![0, 1, 2] => 2 },
+        };
+
+        // {0, 1} has the support of {1}, and {0, 1, 2} that of {0, 2}
+        let expected: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 4,
+                itemset![1] => 3,
+                itemset![2] => 3,
+                itemset![3] => 2,
+            },
+            2 => hashmap! { itemset![0, 2] => 2, itemset![1, 2] => 2 },
+            3 => hashmap! {},
+        };
+        assert_eq!(minimal_generators(&itemsets), expected);
+
+        // a level keyed 0, e.g. the empty itemset, is kept as it is
+        let itemsets: FrequentItemsets = hashmap! {
+            0 => hashmap! { itemset![] => 4 },
+            1 => hashmap! { itemset![0] => 4 },
+        };
+        assert_eq!(minimal_generators(&itemsets), itemsets);
+    }
+
+    #[test]
+    fn test_generate_maximal_itemsets() {
+        let raw_transactions = to_raw(vec![
+            vec!["bread", "milk", "cheese"],
+            vec!["bread", "milk"],
+            vec!["milk", "cheese", "bread"],
+            vec!["milk", "cheese", "yoghurt"],
+            vec!["bread", "jam"],
+            vec!["milk", "bread", "yoghurt"],
+        ]);
+
+        let (maximal, inventory) =
+            generate_maximal_itemsets(raw_transactions.clone(), SupportFraction(0.3));
+        assert_eq!(
+            named(maximal, &inventory),
+            vec![
+                (vec!["bread".to_string(), "cheese".into(), "milk".into()], 2),
+                (vec!["milk".to_string(), "yoghurt".into()], 2),
+            ]
+        );
+
+        for &min_support in &[0.3, 0.5, 0.9] {
+            assert_matches_filter(raw_transactions.clone(), SupportFraction(min_support));
+        }
+    }
+
+    #[test]
+    fn test_generate_maximal_itemsets_dense() {
+        // every transaction drops a few of 12 items, so patterns are long
+        let items: Vec<String> = (0..12).map(|i| format!("item{}", i)).collect();
+        let transactions: Vec<Vec<&str>> = (0..40_usize)
+            .map(|t| {
+                items
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| (t * 7 + i * 5) % 11 > 2 && (t + i) % 13 != 0)
+                    .map(|(_, item)| item.as_str())
+                    .collect()
+            })
+            .collect();
+
+        for &min_support in &[0.2, 0.4, 0.6] {
+            assert_matches_filter(to_raw(transactions.clone()), SupportFraction(min_support));
+        }
+    }
+
+    #[test]
+    fn test_generate_maximal_itemsets_nothing_frequent() {
+        let (maximal, _) =
+            generate_maximal_itemsets(to_raw(vec![vec!["a"], vec!["b"]]), SupportFraction(0.9));
+        assert!(maximal.is_empty());
+        let (maximal, _) = generate_maximal_itemsets(vec![], SupportFraction(0.5));
+        assert!(maximal.is_empty());
+    }
+}
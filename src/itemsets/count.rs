@@ -1,157 +1,980 @@
 #![allow(non_snake_case)]
 
 use crate::{
-    itemsets::search::generate_candidates_from_prev,
+    error::AprioriError,
+    itemsets::{
+        encode::Encoder,
+        options::{is_set, EmptyLevels, MiningOptions, RoundingMode},
+        search::{estimate_candidate_count, generate_candidates_from_prev},
+        source::TransactionSource,
+        support::SupportFraction,
+    },
+    preprocess::{
+        constant_items, oversized_transactions, truncate_transaction, truncate_transactions,
+    },
     types::{
-        FrequentItemsets, Inventory, ItemCounts, ItemId, Itemset, ItemsetCounts, ItemsetLength,
-        RawTransaction, RawTransactionId, ReverseLookup, Transaction,
+        FrequentItemsets, HashState, Inventory, ItemCounts, ItemId, ItemName, Itemset,
+        ItemsetCounts, ItemsetLength, PairCounts, RawTransaction, RawTransactionId, ReverseLookup,
+        Transaction,
     },
 };
-use itertools::{Combinations, Itertools};
+use log::{debug, info, warn};
+#[cfg(feature = "python")]
 use pyo3::prelude::pyfunction;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use std::collections::{hash_map::Keys, HashMap, HashSet};
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::sync::{atomic::AtomicBool, mpsc::Sender};
+use std::time::Duration;
 
-const APPROX_NUM_UNIQUE_ITEMS: usize = 1024; // arbitrary
-const APPROX_NUM_ITEMS_IN_1_TRANSACTION: usize = 16; // arbitrary
+pub(crate) const APPROX_NUM_UNIQUE_ITEMS: usize = 1024; // arbitrary
+pub(crate) const APPROX_NUM_ITEMS_IN_1_TRANSACTION: usize = 16; // arbitrary
 
 /// Generate frequent itemsets from a list of transactions.
 pub fn generate_frequent_itemsets_id(
     raw_transactions: Vec<RawTransactionId>,
-    min_support: f32,
+    min_support: SupportFraction,
     k: ItemsetLength,
 ) -> FrequentItemsets {
-    let mut all_frequent_itemsets: FrequentItemsets = HashMap::with_capacity(k);
-    let N = raw_transactions.len() as f32;
-    let min_support_count = (min_support * N).ceil() as usize;
+    let min_support_count =
+        min_support.support_count(raw_transactions.len(), RoundingMode::default());
 
     // 1-itemset
-    let (item_counts, mut transactions) =
-        generate_frequent_1_itemset_counts_id(raw_transactions, min_support);
-
-    // 2-itemset
-    if k == 1 {
-        let frequent_1_itemset_counts: ItemsetCounts = convert_to_itemset_counts(item_counts);
-        all_frequent_itemsets.insert(1, frequent_1_itemset_counts);
-    } else {
-        transactions.retain(|transaction| transaction.len() >= 2);
-        let candidates = item_counts.keys().combinations(2);
-        let frequent_2_itemset_counts: ItemsetCounts =
-            generate_frequent_2_itemset_counts(
-                candidates,
-                &transactions,
-                min_support_count,
-            );
-        let frequent_1_itemset_counts: ItemsetCounts = convert_to_itemset_counts(item_counts);
-
-        all_frequent_itemsets.insert(1, frequent_1_itemset_counts);
-        all_frequent_itemsets.insert(2, frequent_2_itemset_counts);
-    }
-
-    // k-itemset, k >= 3
-    for size in 3..=k {
-        transactions.retain(|transaction| transaction.len() >= size);
-        let candidates = generate_candidates_from_prev(&all_frequent_itemsets[&(size - 1_usize)]);
-        let frequent_itemset_counts = generate_frequent_k_itemset_counts(
-            candidates,
-            &transactions,
-            min_support_count,
-        );
-
-        all_frequent_itemsets.insert(size, frequent_itemset_counts);
-    }
-
-    all_frequent_itemsets
+    let (item_counts, transactions) =
+        count_frequent_items_id(raw_transactions, min_support_count);
+
+    mine_levels(
+        item_counts,
+        transactions,
+        min_support_count,
+        Some(k),
+        &MiningOptions::default(),
+    )
+    .expect("default options impose no limits")
 }
 
 /// Generate frequent itemsets from a list of transactions.
 pub fn generate_frequent_itemsets(
     raw_transactions: Vec<RawTransaction>,
-    min_support: f32,
+    min_support: SupportFraction,
     k: ItemsetLength,
 ) -> (FrequentItemsets, Inventory) {
-    let mut all_frequent_itemsets: FrequentItemsets = HashMap::with_capacity(k);
-    let N = raw_transactions.len() as f32;
-    let min_support_count = (min_support * N).ceil() as usize;
+    let min_support_count =
+        min_support.support_count(raw_transactions.len(), RoundingMode::default());
+
+    let (all_frequent_itemsets, inventory, _) = mine_transactions(
+        raw_transactions,
+        min_support_count,
+        Some(k),
+        &MiningOptions::default(),
+    )
+    .expect("default options impose no limits");
+
+    (all_frequent_itemsets, inventory)
+}
+
+/// Generate frequent itemsets from a list of transactions, honouring the
+/// limits in `options`.
+///
+/// Alongside the itemsets and inventory, returns the support count of every
+/// frequent item keyed by plain id, the same counts as level 1 without the
+/// `vec![id]` keys.
+///
+/// With `k = None`, levels keep growing until one comes back empty; that
+/// empty level is not included in the result. Pair this with
+/// [`MiningOptions::candidate_budget`] to guard against runaway levels.
+///
+/// Fails with [`AprioriError::ThresholdUnreachable`] if `min_support` asks for
+/// more transactions than there are, which usually means a count was passed
+/// where a fraction was expected. Fails with [`AprioriError::ThresholdTooLow`]
+/// if `k` is `None`, no candidate budget is set and the threshold rounds to
+/// zero transactions, which is most likely a mistake. A threshold of one
+/// transaction is allowed, as small datasets need it, but prunes nothing: every
+/// subset of the longest transaction is enumerated unless the budget stops it.
+pub fn try_generate_frequent_itemsets<'l>(
+    mut raw_transactions: Vec<RawTransaction<'l>>,
+    min_support: SupportFraction,
+    k: Option<ItemsetLength>,
+    options: &MiningOptions,
+) -> Result<(FrequentItemsets, Inventory<'l>, ItemCounts), AprioriError> {
+    let min_support_count =
+        prepare_transactions(&mut raw_transactions, min_support, k, options)?;
+
+    mine_transactions(raw_transactions, min_support_count, k, options)
+}
+
+/// Same as [`try_generate_frequent_itemsets`] for transactions that are
+/// already integer-coded, e.g. keys from a database.
+///
+/// The integers are used as item ids as they are, so no names are interned
+/// and no inventory is built: every id in the result stands for itself.
+pub fn try_generate_frequent_itemsets_id(
+    mut raw_transactions: Vec<RawTransactionId>,
+    min_support: SupportFraction,
+    k: Option<ItemsetLength>,
+    options: &MiningOptions,
+) -> Result<(FrequentItemsets, ItemCounts), AprioriError> {
+    let min_support_count =
+        prepare_transactions(&mut raw_transactions, min_support, k, options)?;
+
+    // 1-itemset
+    let level_1_support_count =
+        options.level_support_count(1, raw_transactions.len(), min_support_count);
+    let (item_counts, transactions) =
+        count_frequent_items_id(raw_transactions, level_1_support_count);
+
+    let all_frequent_itemsets =
+        mine_levels(item_counts.clone(), transactions, min_support_count, k, options)?;
+
+    Ok((all_frequent_itemsets, item_counts))
+}
+
+/// Same as [`try_generate_frequent_itemsets`], leaving out every itemset that
+/// contains an item of `must_exclude`.
+///
+/// The excluded items are dropped after level 1 has been counted, so they
+/// never enter a candidate, but the transactions holding them are all still
+/// mined and N is unchanged: every other itemset keeps the support, count and
+/// fraction it has in the full data. That is not the same as deleting the
+/// items from the transactions beforehand, which empties the transactions
+/// made only of excluded items, and with
+/// [`MiningOptions::min_transaction_len`] drops them and shrinks N.
+pub fn try_generate_frequent_itemsets_excluding<'l>(
+    mut raw_transactions: Vec<RawTransaction<'l>>,
+    min_support: SupportFraction,
+    k: Option<ItemsetLength>,
+    must_exclude: &HashSet<ItemName>,
+    options: &MiningOptions,
+) -> Result<(FrequentItemsets, Inventory<'l>, ItemCounts), AprioriError> {
+    let min_support_count =
+        prepare_transactions(&mut raw_transactions, min_support, k, options)?;
 
     // 1-itemset
-    let (item_counts, inventory, mut transactions) =
-        generate_frequent_1_itemset_counts(raw_transactions, min_support);
-
-    // 2-itemset
-    if k == 1 {
-        let frequent_1_itemset_counts: ItemsetCounts = convert_to_itemset_counts(item_counts);
-        all_frequent_itemsets.insert(1, frequent_1_itemset_counts);
-    } else {
-        transactions.retain(|transaction| transaction.len() >= 2);
-        let candidates = item_counts.keys().combinations(2);
-        let frequent_2_itemset_counts: ItemsetCounts =
-            generate_frequent_2_itemset_counts(
-                candidates,
-                &transactions,
-                min_support_count,
+    let level_1_support_count =
+        options.level_support_count(1, raw_transactions.len(), min_support_count);
+    let (mut item_counts, inventory, transactions) =
+        count_frequent_items(raw_transactions, level_1_support_count);
+    item_counts.retain(|item_id, _| !must_exclude.contains(inventory[item_id]));
+
+    let all_frequent_itemsets =
+        mine_levels(item_counts.clone(), transactions, min_support_count, k, options)?;
+
+    Ok((all_frequent_itemsets, inventory, item_counts))
+}
+
+/// Apply the transaction length options to `raw_transactions`, then turn
+/// `min_support` into a support count and check it can do any good.
+fn prepare_transactions<T>(
+    raw_transactions: &mut Vec<HashSet<T, HashState>>,
+    min_support: SupportFraction,
+    k: Option<ItemsetLength>,
+    options: &MiningOptions,
+) -> Result<usize, AprioriError> {
+    if options.min_transaction_len > 0 {
+        raw_transactions.retain(|transaction| transaction.len() >= options.min_transaction_len);
+    }
+    if let Some(max_len) = options.warn_transaction_len {
+        let oversized = oversized_transactions(raw_transactions, max_len);
+        if !oversized.is_empty() {
+            warn!(
+                "{} transactions have more than {} items: {:?}",
+                oversized.len(),
+                max_len,
+                oversized
             );
-        let frequent_1_itemset_counts: ItemsetCounts = convert_to_itemset_counts(item_counts);
+        }
+    }
+    let N = raw_transactions.len();
+    check_support_count(
+        min_support.support_count(N, options.rounding),
+        N,
+        k,
+        options,
+    )
+}
 
-        all_frequent_itemsets.insert(1, frequent_1_itemset_counts);
-        all_frequent_itemsets.insert(2, frequent_2_itemset_counts);
+/// Check that `min_support_count` out of `N` transactions can do any good,
+/// and pass it on if so.
+pub(crate) fn check_support_count(
+    min_support_count: usize,
+    N: usize,
+    k: Option<ItemsetLength>,
+    options: &MiningOptions,
+) -> Result<usize, AprioriError> {
+    if min_support_count > N {
+        return Err(AprioriError::ThresholdUnreachable {
+            required: min_support_count,
+            n: N,
+        });
+    }
+    if k.is_none() && options.candidate_budget.is_none() && min_support_count == 0 {
+        return Err(AprioriError::ThresholdTooLow {
+            required: min_support_count,
+        });
     }
 
-    // k-itemset, k >= 3
-    for size in 3..=k {
-        transactions.retain(|transaction| transaction.len() >= size);
-        let candidates = generate_candidates_from_prev(&all_frequent_itemsets[&(size - 1_usize)]);
-        let frequent_itemset_counts = generate_frequent_k_itemset_counts(
-            candidates,
-            &transactions,
+    Ok(min_support_count)
+}
+
+fn mine_transactions<'l>(
+    raw_transactions: Vec<RawTransaction<'l>>,
+    min_support_count: usize,
+    k: Option<ItemsetLength>,
+    options: &MiningOptions,
+) -> Result<(FrequentItemsets, Inventory<'l>, ItemCounts), AprioriError> {
+    // 1-itemset
+    let level_1_support_count =
+        options.level_support_count(1, raw_transactions.len(), min_support_count);
+    let (item_counts, inventory, transactions) =
+        count_frequent_items(raw_transactions, level_1_support_count);
+
+    let all_frequent_itemsets =
+        mine_levels(item_counts.clone(), transactions, min_support_count, k, options)?;
+
+    Ok((all_frequent_itemsets, inventory, item_counts))
+}
+
+/// Grow frequent itemsets level by level, starting from the frequent 1-itemsets.
+/// `k = None` grows until a level is empty.
+pub(crate) fn mine_levels(
+    item_counts: ItemCounts,
+    transactions: Vec<Transaction>,
+    min_support_count: usize,
+    k: Option<ItemsetLength>,
+    options: &MiningOptions,
+) -> Result<FrequentItemsets, AprioriError> {
+    collect_levels(
+        LevelIter::new(item_counts, transactions, min_support_count, k, options),
+        options,
+    )
+}
+
+/// Every level of `levels`, leaving out the empty ones unless `options` asks
+/// for them.
+pub(crate) fn collect_levels<S: TransactionSource>(
+    levels: LevelIter<S>,
+    options: &MiningOptions,
+) -> Result<FrequentItemsets, AprioriError> {
+    let mut all_frequent_itemsets: FrequentItemsets = HashMap::default();
+    for level in levels {
+        let (size, itemset_counts) = level?;
+        if options.empty_levels == EmptyLevels::Include || !itemset_counts.is_empty() {
+            all_frequent_itemsets.insert(size, itemset_counts);
+        }
+    }
+
+    Ok(all_frequent_itemsets)
+}
+
+/// Core of [`mine_levels`]: yields every level in ascending order, each one
+/// as soon as the next level's candidates have been generated from it, so
+/// only the level being extended is held at any time. An error ends the
+/// iteration.
+///
+/// Levels are counted over transactions held in memory, or re-read from a
+/// [`TransactionSource`] for every level.
+pub(crate) struct LevelIter<S = Vec<Transaction>> {
+    transactions: Scanned<S>,
+    min_support_count: usize,
+    /// Transaction count before any were dropped for being too short.
+    N: usize,
+    k: Option<ItemsetLength>,
+    options: MiningOptions,
+    stage: Stage,
+}
+
+enum Stage {
+    /// Level 1 has been counted; level 2 is next.
+    Start(ItemCounts),
+    /// Generate the candidates of `size` items from the level below.
+    Extend {
+        size: ItemsetLength,
+        prev: ItemsetCounts,
+    },
+    /// Count the candidates of `size` items.
+    Count {
+        size: ItemsetLength,
+        candidates: Vec<Itemset>,
+        stopwatch: Stopwatch,
+    },
+    Done,
+}
+
+/// Transactions a [`LevelIter`] counts its levels over.
+enum Scanned<S> {
+    /// Held in memory, so infrequent items, the excess of long transactions
+    /// and, at each level, the transactions too short for it are dropped in
+    /// place. Levels are counted in parallel.
+    Memory(Vec<Transaction>),
+    /// Re-read for every level and counted serially.
+    Stream(Stream<S>),
+}
+
+/// A [`TransactionSource`] read one transaction at a time, each pruned and
+/// truncated as it is read, the way [`Scanned::Memory`] transactions are
+/// once up front.
+struct Stream<S> {
+    source: S,
+    /// The frequent items, the only ones kept from each transaction.
+    item_counts: ItemCounts,
+    /// Transactions shorter than this are skipped, as
+    /// [`MiningOptions::min_transaction_len`] drops them.
+    min_len: usize,
+    max_len: Option<usize>,
+    /// Number of transactions not skipped.
+    len: usize,
+}
+
+impl LevelIter {
+    pub(crate) fn new(
+        item_counts: ItemCounts,
+        mut transactions: Vec<Transaction>,
+        min_support_count: usize,
+        k: Option<ItemsetLength>,
+        options: &MiningOptions,
+    ) -> Self {
+        // nothing has been dropped from `transactions` yet, so this is N
+        let N = transactions.len();
+
+        prune_infrequent_from_transactions(&mut transactions, &item_counts);
+        if let Some(max_len) = options.max_transaction_len {
+            truncate_transactions(&mut transactions, &item_counts, max_len);
+        }
+
+        Self::start(
+            Scanned::Memory(transactions),
+            N,
+            item_counts,
+            min_support_count,
+            k,
+            options,
+        )
+    }
+}
+
+impl<S: TransactionSource> LevelIter<S> {
+    /// Mine the levels above `item_counts`, the frequent items of the `N`
+    /// transactions in `source` at least [`MiningOptions::min_transaction_len`]
+    /// long, re-reading `source` for each level.
+    pub(crate) fn from_source(
+        source: S,
+        item_counts: ItemCounts,
+        N: usize,
+        min_support_count: usize,
+        k: Option<ItemsetLength>,
+        options: &MiningOptions,
+    ) -> Self {
+        let stream = Stream {
+            source,
+            item_counts: item_counts.clone(),
+            min_len: options.min_transaction_len,
+            max_len: options.max_transaction_len,
+            len: N,
+        };
+
+        Self::start(
+            Scanned::Stream(stream),
+            N,
+            item_counts,
             min_support_count,
+            k,
+            options,
+        )
+    }
+
+    fn start(
+        transactions: Scanned<S>,
+        N: usize,
+        item_counts: ItemCounts,
+        min_support_count: usize,
+        k: Option<ItemsetLength>,
+        options: &MiningOptions,
+    ) -> Self {
+        // every counted itemset occurs at least once, so 0 would only let
+        // never-seen candidates through
+        let min_support_count = min_support_count.max(1);
+        if min_support_count == 1 {
+            warn!(
+                "a support threshold of 1 transaction prunes nothing: \
+                 every itemset that occurs is frequent"
+            );
+        }
+
+        let constant = constant_items(&item_counts, N);
+        if !constant.is_empty() {
+            warn!(
+                "{} items occur in every transaction: {:?}",
+                constant.len(),
+                constant
+            );
+        }
+        info!(
+            "level 1: {} frequent items in {} transactions (min support count {})",
+            item_counts.len(),
+            transactions.len(),
+            min_support_count
         );
 
-        all_frequent_itemsets.insert(size, frequent_itemset_counts);
+        Self {
+            transactions,
+            min_support_count,
+            N,
+            k,
+            options: options.clone(),
+            stage: Stage::Start(item_counts),
+        }
+    }
+
+    /// Whether the iteration is known to be over. It can still end on the
+    /// next call when that finds an empty level.
+    fn is_done(&self) -> bool {
+        matches!(self.stage, Stage::Done)
     }
 
-    (all_frequent_itemsets, inventory)
+    fn level_support_count(&self, size: ItemsetLength) -> usize {
+        self.options
+            .level_support_count(size, self.N, self.min_support_count)
+            .max(1)
+    }
 }
 
-fn generate_frequent_2_itemset_counts(
-    candidates: Combinations<Keys<usize, u32>>,
-    transactions: &[Transaction],
-    min_support_count: usize,
-) -> ItemsetCounts {
-    candidates
-        .par_bridge()
-        .into_par_iter()
-        .filter_map(|candidate| {
-            let candidate_count = transactions
-                .par_iter()
-                .filter(|transaction| candidate.iter().all(|item| transaction.contains(item)))
-                .count();
-            if candidate_count >= min_support_count {
-                let mut freq: Itemset = candidate.iter().map(|x| **x).collect();
-                freq.sort_unstable();
-                Some((freq, candidate_count as u32))
-            } else {
-                None
+impl<S: TransactionSource> Iterator for LevelIter<S> {
+    type Item = Result<(ItemsetLength, ItemsetCounts), AprioriError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match std::mem::replace(&mut self.stage, Stage::Done) {
+                Stage::Done => return None,
+                Stage::Start(_) | Stage::Extend { .. } if self.options.is_cancelled() => {
+                    return Some(Err(AprioriError::Cancelled));
+                }
+                Stage::Start(item_counts) => {
+                    if self.k == Some(1) {
+                        return Some(Ok((1, convert_to_itemset_counts(item_counts))));
+                    }
+
+                    // 2-itemset
+                    if let Err(err) = check_candidate_budget(2, item_counts.len(), &self.options) {
+                        return Some(Err(err));
+                    }
+                    let stopwatch = Stopwatch::start();
+                    self.transactions.retain_min_len(2);
+                    debug!(
+                        "level 2: counting up to {} pairs over {} transactions",
+                        estimate_candidate_count(item_counts.len()),
+                        self.transactions.len()
+                    );
+                    let frequent_2_itemset_counts = match self.transactions.count_pairs(
+                        &item_counts,
+                        self.level_support_count(2),
+                        self.options.cancel_flag(),
+                    ) {
+                        Ok(itemset_counts) => itemset_counts,
+                        Err(err) => return Some(Err(err.into())),
+                    };
+                    if self.options.is_cancelled() {
+                        return Some(Err(AprioriError::Cancelled));
+                    }
+                    info!(
+                        "level 2: {} frequent itemsets in {:?}",
+                        frequent_2_itemset_counts.len(),
+                        stopwatch.elapsed()
+                    );
+
+                    if self.k.is_some() || !frequent_2_itemset_counts.is_empty() {
+                        self.stage = Stage::Extend {
+                            size: 3,
+                            prev: frequent_2_itemset_counts,
+                        };
+                    }
+                    return Some(Ok((1, convert_to_itemset_counts(item_counts))));
+                }
+                // k-itemset, k >= 3
+                Stage::Extend { size, prev } => {
+                    if self.k.is_some_and(|k| size > k) {
+                        return Some(Ok((size - 1, prev)));
+                    }
+                    if let Err(err) = check_candidate_budget(size, prev.len(), &self.options) {
+                        return Some(Err(err));
+                    }
+                    let stopwatch = Stopwatch::start();
+                    self.transactions.retain_min_len(size);
+                    let candidates =
+                        generate_candidates_from_prev(&prev, self.options.candidate_strategy);
+                    self.stage = Stage::Count {
+                        size,
+                        candidates,
+                        stopwatch,
+                    };
+                    return Some(Ok((size - 1, prev)));
+                }
+                Stage::Count {
+                    size,
+                    candidates,
+                    stopwatch,
+                } => {
+                    debug!(
+                        "level {}: counting {} candidates over {} transactions",
+                        size,
+                        candidates.len(),
+                        self.transactions.len()
+                    );
+                    let frequent_itemset_counts = match self.transactions.count_candidates(
+                        candidates,
+                        size,
+                        self.level_support_count(size),
+                        self.options.cancel_flag(),
+                    ) {
+                        Ok(itemset_counts) => itemset_counts,
+                        Err(err) => return Some(Err(err.into())),
+                    };
+                    if self.options.is_cancelled() {
+                        return Some(Err(AprioriError::Cancelled));
+                    }
+                    info!(
+                        "level {}: {} frequent itemsets in {:?}",
+                        size,
+                        frequent_itemset_counts.len(),
+                        stopwatch.elapsed()
+                    );
+
+                    if self.k.is_none() && frequent_itemset_counts.is_empty() {
+                        return None;
+                    }
+                    self.stage = Stage::Extend {
+                        size: size + 1,
+                        prev: frequent_itemset_counts,
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl<S: TransactionSource> Scanned<S> {
+    /// Number of transactions left, or all of them for a stream, which only
+    /// skips the short ones as they are read.
+    fn len(&self) -> usize {
+        match self {
+            Scanned::Memory(transactions) => transactions.len(),
+            Scanned::Stream(stream) => stream.len,
+        }
+    }
+
+    /// Drop the transactions with fewer than `size` items, which cannot
+    /// contain any itemset from here on.
+    fn retain_min_len(&mut self, size: ItemsetLength) {
+        if let Scanned::Memory(transactions) = self {
+            transactions.retain(|transaction| transaction.len() >= size);
+        }
+    }
+
+    fn count_pairs(
+        &self,
+        item_counts: &ItemCounts,
+        min_support_count: usize,
+        cancel: Option<&AtomicBool>,
+    ) -> io::Result<ItemsetCounts> {
+        let pair_counts = match self {
+            Scanned::Memory(transactions) => count_pairs_until(transactions, item_counts, cancel),
+            Scanned::Stream(stream) => {
+                let mut pair_counts = PairCounts::default();
+                stream.for_each(2, cancel, &mut |transaction| {
+                    add_pairs(&mut pair_counts, transaction, item_counts)
+                })?;
+                pair_counts
+            }
+        };
+
+        Ok(frequent_pairs(pair_counts, min_support_count))
+    }
+
+    fn count_candidates(
+        &self,
+        candidates: Vec<Itemset>,
+        size: ItemsetLength,
+        min_support_count: usize,
+        cancel: Option<&AtomicBool>,
+    ) -> io::Result<ItemsetCounts> {
+        let stream = match self {
+            Scanned::Memory(transactions) => {
+                return Ok(count_candidates_until(
+                    candidates,
+                    transactions,
+                    min_support_count,
+                    cancel,
+                ));
+            }
+            Scanned::Stream(stream) => stream,
+        };
+
+        let mut counts = vec![0_u32; candidates.len()];
+        stream.for_each(size, cancel, &mut |transaction| {
+            for (candidate, count) in candidates.iter().zip(counts.iter_mut()) {
+                if candidate.iter().all(|item| transaction.contains(item)) {
+                    *count += 1;
+                }
+            }
+        })?;
+
+        Ok(candidates
+            .into_iter()
+            .zip(counts)
+            .filter(|&(_, count)| is_frequent(count as usize, min_support_count))
+            .collect())
+    }
+}
+
+impl<S: TransactionSource> Stream<S> {
+    /// Call `f` with every transaction of at least `size` items once pruned
+    /// and truncated. Once `cancel` is set, the rest are skipped.
+    fn for_each(
+        &self,
+        size: ItemsetLength,
+        cancel: Option<&AtomicBool>,
+        f: &mut dyn FnMut(&[ItemId]),
+    ) -> io::Result<()> {
+        let mut transaction: Transaction = Vec::new();
+
+        self.source.for_each(&mut |items| {
+            if items.len() < self.min_len.max(size) || is_set(cancel) {
+                return;
+            }
+            transaction.clear();
+            transaction.extend(
+                items
+                    .iter()
+                    .copied()
+                    .filter(|item| self.item_counts.contains_key(item)),
+            );
+            if let Some(max_len) = self.max_len {
+                truncate_transaction(&mut transaction, &self.item_counts, max_len);
+            }
+            if transaction.len() >= size {
+                f(&transaction);
             }
         })
+    }
+}
+
+/// Frequent itemsets of `raw_transactions`, one level at a time.
+///
+/// Yields the same levels as [`generate_frequent_itemsets`], in ascending
+/// order, but computes each one only when asked for it and holds on to no
+/// more than the level needed to generate the next candidates. Levels can be
+/// written out or otherwise consumed as they arrive, without the whole result
+/// ever being in memory.
+pub fn levels_iter(
+    raw_transactions: Vec<RawTransaction<'_>>,
+    min_support: SupportFraction,
+    k: ItemsetLength,
+) -> Levels<'_> {
+    let min_support_count =
+        min_support.support_count(raw_transactions.len(), RoundingMode::default());
+    let (item_counts, inventory, transactions) =
+        count_frequent_items(raw_transactions, min_support_count);
+
+    Levels {
+        inner: LevelIter::new(
+            item_counts,
+            transactions,
+            min_support_count,
+            Some(k),
+            &MiningOptions::default(),
+        ),
+        inventory,
+    }
+}
+
+/// Iterator returned by [`levels_iter`].
+pub struct Levels<'l> {
+    inner: LevelIter,
+    inventory: Inventory<'l>,
+}
+
+impl<'l> Levels<'l> {
+    /// Names of the item ids in the yielded itemsets. Complete from the
+    /// start: every item is encoded before the first level is yielded.
+    pub fn inventory(&self) -> &Inventory<'l> {
+        &self.inventory
+    }
+
+    /// [`inventory`](Self::inventory), once the levels are no longer needed.
+    pub(crate) fn into_inventory(self) -> Inventory<'l> {
+        self.inventory
+    }
+}
+
+impl Iterator for Levels<'_> {
+    type Item = (ItemsetLength, ItemsetCounts);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|level| level.expect("default options impose no limits"))
+    }
+}
+
+/// Mine frequent itemsets of up to `k` items, sending each level over `tx` as
+/// soon as it is complete, in ascending order, so that another thread can
+/// consume it while deeper levels are counted.
+///
+/// The levels are those of [`generate_frequent_itemsets`], as from
+/// [`levels_iter`]. The inventory resolving their item ids is returned once
+/// mining ends. If the receiver hangs up, mining stops early.
+pub fn mine_to_channel(
+    raw_transactions: Vec<RawTransaction<'_>>,
+    min_support: SupportFraction,
+    k: ItemsetLength,
+    tx: Sender<(ItemsetLength, ItemsetCounts)>,
+) -> Inventory<'_> {
+    let mut levels = levels_iter(raw_transactions, min_support, k);
+    for level in levels.by_ref() {
+        if tx.send(level).is_err() {
+            break;
+        }
+    }
+    levels.inventory
+}
+
+/// Result of [`generate_frequent_itemsets_within`].
+#[derive(Debug)]
+pub struct TimedItemsets<'l> {
+    /// Every level completed before the deadline.
+    pub itemsets: FrequentItemsets,
+    pub inventory: Inventory<'l>,
+    /// Whether mining stopped at the deadline with levels left to mine.
+    pub timed_out: bool,
+}
+
+/// Same as [`generate_frequent_itemsets`], but once `timeout` has passed no
+/// further level is mined, and the levels done so far are returned instead.
+///
+/// The deadline is checked between levels, so only fully completed levels
+/// are returned, each of them exact: the levels from the one that was due
+/// next are simply missing. Level 1 is always returned. A run can overrun
+/// `timeout` by up to the time one level takes. On wasm32-unknown-unknown,
+/// which has no clock, only a zero timeout takes effect.
+pub fn generate_frequent_itemsets_within(
+    raw_transactions: Vec<RawTransaction<'_>>,
+    min_support: SupportFraction,
+    k: ItemsetLength,
+    timeout: Duration,
+) -> TimedItemsets<'_> {
+    let stopwatch = Stopwatch::start();
+    let mut levels = levels_iter(raw_transactions, min_support, k);
+    let mut itemsets: FrequentItemsets = HashMap::default();
+    let mut timed_out = false;
+
+    while let Some((size, itemset_counts)) = levels.next() {
+        itemsets.insert(size, itemset_counts);
+        if stopwatch.elapsed() >= timeout && !levels.inner.is_done() {
+            timed_out = true;
+            break;
+        }
+    }
+
+    TimedItemsets {
+        itemsets,
+        inventory: levels.inventory,
+        timed_out,
+    }
+}
+
+/// Remove the ids of infrequent items from every transaction.
+///
+/// Such items cannot be part of any frequent itemset, so dropping them after
+/// the 1-itemset filter shrinks every later containment check, and lets the
+/// per-level length filter discard more transactions.
+pub fn prune_infrequent_from_transactions(
+    transactions: &mut [Transaction],
+    item_counts: &ItemCounts,
+) {
+    let prune = |transaction: &mut Transaction| {
+        transaction.retain(|item| item_counts.contains_key(item));
+    };
+
+    #[cfg(feature = "parallel")]
+    transactions.par_iter_mut().for_each(prune);
+    #[cfg(not(feature = "parallel"))]
+    transactions.iter_mut().for_each(prune);
+}
+
+/// Wall-clock timer for the log output. `Instant` is not available on
+/// wasm32-unknown-unknown, so elapsed times are reported as zero there.
+struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    fn start() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.start.elapsed()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Duration::ZERO
+        }
+    }
+}
+
+/// Refuse to generate a level whose projected candidate count is over budget.
+fn check_candidate_budget(
+    level: ItemsetLength,
+    prev_level_size: usize,
+    options: &MiningOptions,
+) -> Result<(), AprioriError> {
+    if let Some(budget) = options.candidate_budget {
+        let estimate = estimate_candidate_count(prev_level_size);
+        if estimate > budget {
+            return Err(AprioriError::CandidateBudgetExceeded {
+                level,
+                estimate,
+                budget,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The pairs of `pair_counts` counted at least `min_support_count` times.
+fn frequent_pairs(pair_counts: PairCounts, min_support_count: usize) -> ItemsetCounts {
+    pair_counts
+        .into_iter()
+        .filter(|&(_, count)| is_frequent(count as usize, min_support_count))
+        .map(|((a, b), count)| (Itemset::from(&[a, b][..]), count))
         .collect()
 }
 
+/// Transactions per rayon task in [`count_pairs`], and between checks of the
+/// cancel flag. Each task owns a local map, so small chunks spend more time
+/// merging than counting.
+const PAIR_COUNTING_CHUNK_SIZE: usize = 4096;
+
+/// Count every pair of frequent items that co-occur in a transaction.
+///
+/// Each transaction only enumerates the pairs it actually contains, instead of
+/// scanning all transactions for every candidate pair. With the `parallel`
+/// feature, transactions are split across threads that each fill a local map,
+/// and the maps are merged by summing counts.
+pub fn count_pairs(transactions: &[Transaction], item_counts: &ItemCounts) -> PairCounts {
+    count_pairs_until(transactions, item_counts, None)
+}
+
+/// [`count_pairs`], skipping every chunk of transactions not yet counted once
+/// `cancel` is set. The counts are then incomplete, and only good for
+/// throwing away.
+fn count_pairs_until(
+    transactions: &[Transaction],
+    item_counts: &ItemCounts,
+    cancel: Option<&AtomicBool>,
+) -> PairCounts {
+    #[cfg(feature = "parallel")]
+    {
+        transactions
+            .par_chunks(PAIR_COUNTING_CHUNK_SIZE)
+            .map(|chunk| {
+                if is_set(cancel) {
+                    return PairCounts::default();
+                }
+                count_pairs_serial(chunk, item_counts)
+            })
+            .reduce(PairCounts::default, |a, b| {
+                if a.len() < b.len() {
+                    return merge_pair_counts(b, a);
+                }
+                merge_pair_counts(a, b)
+            })
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut pair_counts = PairCounts::default();
+        for chunk in transactions.chunks(PAIR_COUNTING_CHUNK_SIZE) {
+            if is_set(cancel) {
+                break;
+            }
+            for transaction in chunk {
+                add_pairs(&mut pair_counts, transaction, item_counts);
+            }
+        }
+        pair_counts
+    }
+}
+
+/// Single-threaded [`count_pairs`].
+pub fn count_pairs_serial(transactions: &[Transaction], item_counts: &ItemCounts) -> PairCounts {
+    let mut pair_counts = PairCounts::default();
+    for transaction in transactions {
+        add_pairs(&mut pair_counts, transaction, item_counts);
+    }
+    pair_counts
+}
+
+fn add_pairs(pair_counts: &mut PairCounts, transaction: &[ItemId], item_counts: &ItemCounts) {
+    let items: Vec<ItemId> = transaction
+        .iter()
+        .copied()
+        .filter(|item| item_counts.contains_key(item))
+        .collect();
+
+    // transactions are sorted, so every pair comes out sorted too
+    for (i, &a) in items.iter().enumerate() {
+        for &b in &items[(i + 1)..] {
+            *pair_counts.entry((a, b)).or_insert(0) += 1;
+        }
+    }
+}
+
+fn merge_pair_counts(mut into: PairCounts, from: PairCounts) -> PairCounts {
+    for (pair, count) in from {
+        *into.entry(pair).or_insert(0) += count;
+    }
+    into
+}
+
 /// includes pruning
 fn generate_frequent_k_itemset_counts(
     candidate_counts: Vec<Itemset>,
     transactions: &[Transaction],
     min_support_count: usize,
 ) -> ItemsetCounts {
+    count_candidates_until(candidate_counts, transactions, min_support_count, None)
+}
+
+/// [`generate_frequent_k_itemset_counts`], skipping every candidate not yet
+/// counted once `cancel` is set. The counts are then incomplete, and only good
+/// for throwing away.
+fn count_candidates_until(
+    candidate_counts: Vec<Itemset>,
+    transactions: &[Transaction],
+    min_support_count: usize,
+    cancel: Option<&AtomicBool>,
+) -> ItemsetCounts {
+    // candidates are moved into the result, not copied
+    #[cfg(feature = "parallel")]
+    let candidate_counts = candidate_counts.into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let candidate_counts = candidate_counts.into_iter();
+
     candidate_counts
-        .par_iter()
         .filter_map(|candidate| {
-            let candidate_count = transactions
-                .par_iter()
-                .filter(|transaction| candidate.iter().all(|item| transaction.contains(item)))
-                .count();
-            if candidate_count >= min_support_count {
-                Some((candidate.iter().copied().collect(), candidate_count as u32))
+            if is_set(cancel) {
+                return None;
+            }
+            let candidate_count = count_containing(transactions, &candidate);
+            if is_frequent(candidate_count, min_support_count) {
+                Some((candidate, candidate_count as u32))
             } else {
                 None
             }
@@ -159,21 +982,131 @@ fn generate_frequent_k_itemset_counts(
         .collect()
 }
 
+/// Support count of each of `candidates` in `transactions`, with no threshold
+/// applied: candidates that never occur are present with a count of 0.
+///
+/// This is the counting half of mining on its own, for checking specific
+/// itemsets without generating any candidates. Candidates may be of any
+/// length, and need only use the same ids as the transactions.
+pub fn count_itemsets(transactions: &[Transaction], candidates: Vec<Itemset>) -> ItemsetCounts {
+    generate_frequent_k_itemset_counts(candidates, transactions, 0)
+}
+
+/// Mine the `sample` of `raw_transactions` at `loose_support`, then recount
+/// the itemsets found over all of `raw_transactions` with [`count_itemsets`]
+/// and keep those meeting `strict_support`.
+///
+/// `sample` holds indices into `raw_transactions`, and `loose_support` is a
+/// fraction of the sample, `strict_support` of all the transactions. The first
+/// phase only reads the sample, so it is cheap; the second counts the first
+/// phase's itemsets exactly, generating no candidates of its own, and drops
+/// those the sample overrated. Every itemset returned is frequent at
+/// `strict_support`, with its exact count. An itemset frequent in the full
+/// data but below `loose_support` in the sample is missed, so the loose
+/// threshold should sit some way under the strict one; with the whole dataset
+/// as the sample and `loose_support` not above `strict_support`, the result
+/// is the same as mining at `strict_support` directly.
+///
+/// # Panics
+///
+/// If an index in `sample` is out of range.
+pub fn two_phase_mine<'l>(
+    raw_transactions: Vec<RawTransaction<'l>>,
+    sample: &[usize],
+    loose_support: SupportFraction,
+    strict_support: SupportFraction,
+    k: ItemsetLength,
+) -> (FrequentItemsets, Inventory<'l>) {
+    let N = raw_transactions.len();
+    let loose_support_count = loose_support.support_count(sample.len(), RoundingMode::default());
+    let strict_support_count = strict_support.support_count(N, RoundingMode::default());
+
+    let (_, inventory, transactions) = count_items(raw_transactions);
+    let sample: Vec<Transaction> = sample.iter().map(|&tid| transactions[tid].clone()).collect();
+
+    // phase 1, on the sample
+    let mut item_counts: ItemCounts = HashMap::default();
+    for &item in sample.iter().flatten() {
+        *item_counts.entry(item).or_insert(0) += 1;
+    }
+    item_counts.retain(|_, &mut support_count| {
+        is_frequent(support_count as usize, loose_support_count)
+    });
+    let candidates = mine_levels(
+        item_counts,
+        sample,
+        loose_support_count,
+        Some(k),
+        &MiningOptions::default(),
+    )
+    .expect("default options impose no limits");
+
+    // phase 2, on every transaction
+    let itemsets = candidates
+        .into_iter()
+        .map(|(size, itemset_counts)| {
+            let mut itemset_counts =
+                count_itemsets(&transactions, itemset_counts.into_keys().collect());
+            itemset_counts
+                .retain(|_, &mut count| is_frequent(count as usize, strict_support_count));
+            (size, itemset_counts)
+        })
+        .collect();
+
+    (itemsets, inventory)
+}
+
+/// The one support test every level applies, so that level 1 and the levels
+/// above it agree on what `min_support_count` admits.
+pub(crate) fn is_frequent(support_count: usize, min_support_count: usize) -> bool {
+    support_count >= min_support_count
+}
+
+/// Number of transactions containing every item of `candidate`.
+/// Scans the transactions in parallel when the `parallel` feature is on.
+pub(crate) fn count_containing<T: Borrow<ItemId> + Sync>(
+    transactions: &[Transaction],
+    candidate: &[T],
+) -> usize {
+    #[cfg(feature = "parallel")]
+    let transactions = transactions.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let transactions = transactions.iter();
+
+    transactions
+        .filter(|transaction| {
+            candidate
+                .iter()
+                .all(|item| transaction.contains(item.borrow()))
+        })
+        .count()
+}
+
 fn convert_to_itemset_counts(item_counts: ItemCounts) -> ItemsetCounts {
-    item_counts.into_iter().map(|(k, v)| (vec![k], v)).collect()
+    item_counts
+        .into_iter()
+        .map(|(k, v)| (Itemset::from(&[k][..]), v))
+        .collect()
 }
 
 /// 1-itemset
 /// space: O(2n)
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn generate_frequent_1_itemset_counts_id(
-    raw_transactions: Vec<HashSet<ItemId>>,
-    min_support: f32,
+    raw_transactions: Vec<RawTransactionId>,
+    min_support: SupportFraction,
 ) -> (ItemCounts, Vec<Transaction>) {
-    let N = raw_transactions.len() as f32;
+    let min_support_count =
+        min_support.support_count(raw_transactions.len(), RoundingMode::default());
+    count_frequent_items_id(raw_transactions, min_support_count)
+}
 
-    let mut item_counts = HashMap::with_capacity(APPROX_NUM_UNIQUE_ITEMS);
-    let min_support_count = (min_support * N).ceil() as u32;
+fn count_frequent_items_id(
+    raw_transactions: Vec<RawTransactionId>,
+    min_support_count: usize,
+) -> (ItemCounts, Vec<Transaction>) {
+    let mut item_counts =
+        HashMap::with_capacity_and_hasher(APPROX_NUM_UNIQUE_ITEMS, Default::default());
 
     // Update counts
     let transactions_new: Vec<Transaction> = raw_transactions
@@ -191,59 +1124,126 @@ pub fn generate_frequent_1_itemset_counts_id(
         .collect();
 
     // Prune
-    item_counts.retain(|_, &mut support_count| support_count >= min_support_count);
+    item_counts.retain(|_, &mut support_count| {
+        is_frequent(support_count as usize, min_support_count)
+    });
 
     (item_counts, transactions_new)
 }
 
 /// 1-itemset
 /// space: O(2n)
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn generate_frequent_1_itemset_counts(
-    raw_transactions: Vec<HashSet<&str>>,
-    min_support: f32,
-) -> (ItemCounts, Inventory, Vec<Transaction>) {
-    let N = raw_transactions.len() as f32;
-
-    let mut reverse_lookup: ReverseLookup = HashMap::with_capacity(APPROX_NUM_UNIQUE_ITEMS);
-    let mut inventory: Inventory = HashMap::with_capacity(APPROX_NUM_UNIQUE_ITEMS);
-    let mut last_item_id = 0;
-    let mut item_counts = HashMap::with_capacity(APPROX_NUM_UNIQUE_ITEMS);
-    let mut items = Vec::with_capacity(APPROX_NUM_ITEMS_IN_1_TRANSACTION);
-    let min_support_count = (min_support * N).ceil() as u32;
+    raw_transactions: Vec<RawTransaction<'_>>,
+    min_support: SupportFraction,
+) -> (ItemCounts, Inventory<'_>, Vec<Transaction>) {
+    let min_support_count =
+        min_support.support_count(raw_transactions.len(), RoundingMode::default());
+    count_frequent_items(raw_transactions, min_support_count)
+}
 
-    // Update counts
-    let transactions_new: Vec<Transaction> = raw_transactions
-        .iter()
-        .map(|raw_transaction| {
-            items.clear();
+/// The frequent item counts and inventory of
+/// [`generate_frequent_1_itemset_counts`], without encoding the transactions.
+///
+/// Only the counts, inventory and lookup are held, never a `Vec<Transaction>`
+/// as large as the input, for when the 1-itemset profile is all that is wanted.
+pub fn frequent_item_counts_only<'l>(
+    raw_transactions: &[RawTransaction<'l>],
+    min_support: SupportFraction,
+) -> (ItemCounts, Inventory<'l>) {
+    let min_support_count =
+        min_support.support_count(raw_transactions.len(), RoundingMode::default());
+    let mut encoder = Encoder::new();
+    for raw_transaction in raw_transactions {
+        encoder.count(raw_transaction);
+    }
+    let (mut item_counts, inventory) = encoder.finish();
 
-            for &item in raw_transaction {
-                let item_id: ItemId;
-
-                if reverse_lookup.contains_key(item) {
-                    item_id = *reverse_lookup.get(&item).unwrap();
-                    items.push(item_id);
-                } else {
-                    item_id = last_item_id;
-                    reverse_lookup.insert(item, item_id);
-                    inventory.insert(item_id, item);
-                    items.push(item_id);
-                    last_item_id += 1;
-                }
+    // Prune
+    item_counts.retain(|_, &mut support_count| {
+        is_frequent(support_count as usize, min_support_count)
+    });
 
-                let count = item_counts.entry(item_id).or_insert(0);
-                *count += 1;
-            }
+    (item_counts, inventory)
+}
 
-            items.sort_unstable();
+pub(crate) fn count_frequent_items(
+    raw_transactions: Vec<RawTransaction<'_>>,
+    min_support_count: usize,
+) -> (ItemCounts, Inventory<'_>, Vec<Transaction>) {
+    let (mut item_counts, inventory, transactions) = count_items(raw_transactions);
 
-            items.to_owned()
-        })
+    // Prune
+    item_counts.retain(|_, &mut support_count| {
+        is_frequent(support_count as usize, min_support_count)
+    });
+
+    (item_counts, inventory, transactions)
+}
+
+/// Same as [`generate_frequent_1_itemset_counts`], also returning the names of
+/// the items dropped for appearing in fewer than `min_support * N` transactions.
+pub fn generate_frequent_1_itemset_counts_with_rejected(
+    raw_transactions: Vec<RawTransaction<'_>>,
+    min_support: SupportFraction,
+) -> (ItemCounts, Inventory<'_>, Vec<Transaction>, HashSet<ItemName<'_>>) {
+    let min_support_count =
+        min_support.support_count(raw_transactions.len(), RoundingMode::default());
+    let (mut item_counts, inventory, transactions) = count_items(raw_transactions);
+
+    let rejected = item_counts
+        .iter()
+        .filter(|(_, &support_count)| !is_frequent(support_count as usize, min_support_count))
+        .map(|(item_id, _)| inventory[item_id])
         .collect();
+    item_counts.retain(|_, &mut support_count| {
+        is_frequent(support_count as usize, min_support_count)
+    });
+
+    (item_counts, inventory, transactions, rejected)
+}
+
+/// Same as [`generate_frequent_1_itemset_counts`], but items already in `seed`
+/// keep their ids. New items get fresh ids after the largest seeded id.
+/// The returned inventory includes every seeded item, so several datasets
+/// encoded with the same seed share one id space.
+pub fn generate_frequent_1_itemset_counts_seeded<'l>(
+    raw_transactions: Vec<RawTransaction<'l>>,
+    min_support: SupportFraction,
+    seed: &ReverseLookup<'l>,
+) -> (ItemCounts, Inventory<'l>, Vec<Transaction>) {
+    let min_support_count =
+        min_support.support_count(raw_transactions.len(), RoundingMode::default());
+    let (mut item_counts, inventory, transactions) = count_items_seeded(raw_transactions, seed);
 
     // Prune
-    item_counts.retain(|_, &mut support_count| support_count >= min_support_count);
+    item_counts.retain(|_, &mut support_count| {
+        is_frequent(support_count as usize, min_support_count)
+    });
+
+    (item_counts, inventory, transactions)
+}
+
+/// Encode transactions into item ids and count every item, without pruning.
+pub(crate) fn count_items(
+    raw_transactions: Vec<RawTransaction<'_>>,
+) -> (ItemCounts, Inventory<'_>, Vec<Transaction>) {
+    count_items_seeded(raw_transactions, &ReverseLookup::default())
+}
+
+fn count_items_seeded<'l>(
+    raw_transactions: Vec<RawTransaction<'l>>,
+    seed: &ReverseLookup<'l>,
+) -> (ItemCounts, Inventory<'l>, Vec<Transaction>) {
+    let mut encoder = Encoder::seeded(seed);
+
+    // consuming the raw transactions frees each one as soon as it is encoded
+    let transactions_new: Vec<Transaction> = raw_transactions
+        .into_iter()
+        .map(|raw_transaction| encoder.encode(&raw_transaction))
+        .collect();
+    let (item_counts, inventory) = encoder.finish();
 
     (item_counts, inventory, transactions_new)
 }
@@ -251,17 +1251,18 @@ pub fn generate_frequent_1_itemset_counts(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use maplit::hashmap;
+    use crate::{itemsets::options::CandidateStrategy, result::MiningResult};
 
     const A: &str = "Item A";
     const B: &str = "Item B";
     const C: &str = "Item C";
     const D: &str = "Item D";
+    const E: &str = "Item E";
 
     macro_rules! raw_transaction {
         ($($x:expr),*) => {
             {
-                let mut set: HashSet<_> = HashSet::new();
+                let mut set = HashSet::default();
                 $(set.insert($x);)*
                 set
             }
@@ -271,7 +1272,7 @@ mod tests {
     macro_rules! hashset {
         ($($x:expr),*) => {
             {
-                let mut set: HashSet<_> = HashSet::new();
+                let mut set = HashSet::default();
                 $(set.insert($x);)*
                 set
             }
@@ -281,7 +1282,7 @@ mod tests {
     macro_rules! sorted_vec {
         ($($x:expr),*) => {
             {
-                let mut vec: Itemset = Vec::with_capacity(5);
+                let mut vec: Vec<ItemId> = Vec::with_capacity(5);
                 $(vec.push($x);)*
                 vec.sort_unstable();
                 vec
@@ -289,26 +1290,36 @@ mod tests {
         };
     }
 
+    macro_rules! sorted_itemset {
+        ($($x:expr),*) => {
+            {
+                let mut itemset = itemset![$($x),*];
+                itemset.sort_unstable();
+                itemset
+            }
+        };
+    }
+
     #[test]
     fn update_counts() {
         let transactions = vec![vec![0, 1]];
-        let candidate_counts = vec![vec![0], vec![1]];
+        let candidate_counts = vec![itemset![0], itemset![1]];
 
         let frequent_itemsets =
             generate_frequent_k_itemset_counts(candidate_counts, &transactions, 0);
 
-        assert_eq!(frequent_itemsets, hashmap! { vec![0] => 1, vec![1] => 1 });
+        assert_eq!(frequent_itemsets, hashmap! { itemset![0] => 1, itemset![1] => 1 });
     }
 
     #[test]
     fn update_counts_with_min_support_1() {
         let transactions = vec![vec![10, 11], vec![10, 12]];
-        let candidate_counts = vec![vec![10], vec![11]];
+        let candidate_counts = vec![itemset![10], itemset![11]];
 
         let frequent_itemsets =
             generate_frequent_k_itemset_counts(candidate_counts, &transactions, 2);
 
-        assert_eq!(frequent_itemsets, hashmap! {vec![10] => 2})
+        assert_eq!(frequent_itemsets, hashmap! {itemset![10] => 2})
     }
 
     #[test]
@@ -321,7 +1332,7 @@ mod tests {
             vec![10, 12],
             vec![11, 12],
         ];
-        let candidate_counts = vec![vec![10], vec![11], vec![12], vec![15]];
+        let candidate_counts = vec![itemset![10], itemset![11], itemset![12], itemset![15]];
 
         let frequent_itemsets =
             generate_frequent_k_itemset_counts(candidate_counts, &transactions, 3);
@@ -329,8 +1340,8 @@ mod tests {
         assert_eq!(
             frequent_itemsets,
             hashmap! {
-            vec![10] => 5,
-            vec![12] => 4,
+            itemset![10] => 5,
+            itemset![12] => 4,
             }
         );
     }
@@ -346,53 +1357,53 @@ mod tests {
             vec![11, 13],
         ];
         let candidate_counts = vec![
-            vec![10, 11],
-            vec![10, 13],
-            vec![10, 15],
-            vec![11, 13],
-            vec![11, 15],
+            itemset![10, 11],
+            itemset![10, 13],
+            itemset![10, 15],
+            itemset![11, 13],
+            itemset![11, 15],
         ];
         let frequent_itemsets =
             generate_frequent_k_itemset_counts(candidate_counts, &transactions, 3);
-        assert_eq!(frequent_itemsets, hashmap! { vec![10, 13] => 3});
+        assert_eq!(frequent_itemsets, hashmap! { itemset![10, 13] => 3});
     }
 
     #[test]
     fn update_counts_with_min_support() {
         let transactions = vec![vec![10, 11], vec![10, 13]];
-        let candidate_counts = vec![vec![10], vec![11]];
+        let candidate_counts = vec![itemset![10], itemset![11]];
 
         let frequent_itemsets =
             generate_frequent_k_itemset_counts(candidate_counts, &transactions, 2);
 
-        assert_eq!(frequent_itemsets, hashmap! { vec![10] => 2 });
+        assert_eq!(frequent_itemsets, hashmap! { itemset![10] => 2 });
     }
 
     #[test]
     fn update_counts_2() {
         let transactions = vec![vec![10, 11, 13]];
-        let candidate_counts = vec![vec![10], vec![11]];
+        let candidate_counts = vec![itemset![10], itemset![11]];
 
         let frequent_itemsets =
             generate_frequent_k_itemset_counts(candidate_counts, &transactions, 0);
         assert_eq!(
             frequent_itemsets,
-            hashmap! { vec![10] => 1,
-            vec![11] => 1}
+            hashmap! { itemset![10] => 1,
+            itemset![11] => 1}
         );
     }
 
     #[test]
     fn update_counts_3() {
         let transactions = vec![vec![10, 11, 13], vec![10]];
-        let candidate_counts = vec![vec![10], vec![11]];
+        let candidate_counts = vec![itemset![10], itemset![11]];
 
         let frequent_itemsets =
             generate_frequent_k_itemset_counts(candidate_counts, &transactions, 0);
         assert_eq!(
             frequent_itemsets,
-            hashmap! { vec![10] => 2,
-            vec![11] => 1}
+            hashmap! { itemset![10] => 2,
+            itemset![11] => 1}
         );
     }
 
@@ -400,7 +1411,7 @@ mod tests {
     fn create_counts_one_itemset_with_sorted_transaction_ids() {
         let raw_transactions = vec![raw_transaction![A, B, D], raw_transaction![A]];
         let (itemset_counts, inventory, transaction_ids) =
-            generate_frequent_1_itemset_counts(raw_transactions, 0.0);
+            generate_frequent_1_itemset_counts(raw_transactions, SupportFraction(0.0));
         let lookup = get_reverse_lookup(inventory);
 
         assert_eq!(itemset_counts.len(), 3);
@@ -420,7 +1431,8 @@ mod tests {
     #[test]
     fn create_counts_one_itemset_with_min_support_1() {
         let raw_transactions = vec![raw_transaction![A, B, D], raw_transaction![A]];
-        let (itemset_counts, inventory, _) = generate_frequent_1_itemset_counts(raw_transactions, 1.0);
+        let (itemset_counts, inventory, _) =
+            generate_frequent_1_itemset_counts(raw_transactions, SupportFraction(1.0));
         let lookup = get_reverse_lookup(inventory);
 
         assert_eq!(itemset_counts.len(), 1);
@@ -435,7 +1447,8 @@ mod tests {
             raw_transaction![B],
             raw_transaction![A, C],
         ];
-        let (itemset_counts, inventory, _) = generate_frequent_1_itemset_counts(raw_transactions, 0.5);
+        let (itemset_counts, inventory, _) =
+            generate_frequent_1_itemset_counts(raw_transactions, SupportFraction(0.5));
         let lookup = get_reverse_lookup(inventory);
 
         assert_eq!(itemset_counts.len(), 3);
@@ -445,18 +1458,163 @@ mod tests {
     }
 
     #[test]
-    fn test_convert_to_itemset_counts() {
-        let item_counts: ItemCounts = hashmap! {
-            13 => 3,
-            10 => 0,
-            11 => 5,
-        };
-        let itemset_counts = convert_to_itemset_counts(item_counts);
-
+    fn create_counts_one_itemset_with_rejected() {
+        let raw_transactions = vec![
+            raw_transaction![A, B, C],
+            raw_transaction![A],
+            raw_transaction![B],
+            raw_transaction![A, D],
+        ];
+        let (itemset_counts, inventory, _, rejected) =
+            generate_frequent_1_itemset_counts_with_rejected(
+                raw_transactions,
+                SupportFraction(0.5),
+            );
+        let lookup = get_reverse_lookup(inventory);
+
+        assert_eq!(rejected, hashset![C, D]);
+        assert_eq!(itemset_counts.len(), 2);
+        assert!(!itemset_counts.contains_key(&lookup[C]));
+        assert!(!itemset_counts.contains_key(&lookup[D]));
+    }
+
+    #[test]
+    fn create_counts_one_itemset_seeded() {
+        let seed: ReverseLookup = hashmap! { B => 7, D => 3 };
+        let raw_transactions = vec![raw_transaction![A, B, C], raw_transaction![A, B]];
+        let (itemset_counts, inventory, transaction_ids) =
+            generate_frequent_1_itemset_counts_seeded(
+                raw_transactions,
+                SupportFraction(0.0),
+                &seed,
+            );
+        let lookup = get_reverse_lookup(inventory);
+
+        // seeded items keep their ids, new ones continue after the largest
+        assert_eq!(lookup[B], 7);
+        assert_eq!(lookup[D], 3);
+        assert_eq!(sorted_vec![lookup[A], lookup[C]], vec![8, 9]);
+
+        assert_eq!(itemset_counts.len(), 3);
+        assert_eq!(itemset_counts[&7], 2);
+        assert!(!itemset_counts.contains_key(&3));
+        assert_eq!(transaction_ids[1], sorted_vec![lookup[A], 7]);
+    }
+
+    #[test]
+    fn test_prune_infrequent_from_transactions() {
+        let raw_transactions = vec![
+            hashset![A, B, C],
+            hashset![A, B],
+            hashset![A, D],
+            hashset![B, C],
+        ];
+        let (item_counts, inventory, transactions) =
+            generate_frequent_1_itemset_counts(raw_transactions, SupportFraction(0.5));
+        let id = |name| *inventory.iter().find(|(_, &n)| n == name).unwrap().0;
+
+        let mut pruned = transactions.clone();
+        prune_infrequent_from_transactions(&mut pruned, &item_counts);
+
+        let total = |transactions: &[Transaction]| transactions.iter().map(Vec::len).sum::<usize>();
+        assert_eq!(total(&transactions), 9);
+        assert_eq!(total(&pruned), 8);
+        assert!(pruned.iter().all(|transaction| !transaction.contains(&id(D))));
+
+        // counts of itemsets made of frequent items are unchanged
+        assert_eq!(
+            count_pairs_serial(&pruned, &item_counts),
+            count_pairs_serial(&transactions, &item_counts)
+        );
+        let abc = sorted_vec![id(A), id(B), id(C)];
+        assert_eq!(count_containing(&pruned, &abc), count_containing(&transactions, &abc));
+    }
+
+    #[test]
+    fn test_infrequent_items_never_reach_higher_levels() {
+        use itertools::Itertools;
+
+        // E sits in the big baskets next to every frequent item but is itself
+        // infrequent, so no itemset containing it may appear at any level
+        let raw_transactions = vec![
+            hashset![A, B, C, D, E],
+            hashset![A, B, C, D],
+            hashset![A, B, C],
+            hashset![A, B, D],
+            hashset![B, C, D, E],
+            hashset![A, C, D],
+        ];
+        let (frequent_itemsets, inventory) =
+            generate_frequent_itemsets(raw_transactions.clone(), SupportFraction(0.5), 5);
+        let lookup = get_reverse_lookup(inventory);
+
+        assert!(frequent_itemsets
+            .values()
+            .flat_map(|itemset_counts| itemset_counts.keys())
+            .all(|itemset| !itemset.contains(&lookup[E])));
+
+        // every level agrees with counting all subsets of every basket
+        let mut brute_force: FrequentItemsets = HashMap::default();
+        for transaction in &raw_transactions {
+            let items: Vec<ItemId> = transaction.iter().map(|item| lookup[item]).collect();
+            for size in 1..=items.len() {
+                for mut itemset in items.iter().copied().combinations(size) {
+                    itemset.sort_unstable();
+                    *brute_force
+                        .entry(size)
+                        .or_default()
+                        .entry(Itemset::from(itemset.as_slice()))
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+        for size in 1..=5 {
+            let mut expected: ItemsetCounts = brute_force.remove(&size).unwrap_or_default();
+            expected.retain(|_, &mut count| count >= 3);
+            assert_eq!(frequent_itemsets[&size], expected, "level {}", size);
+        }
+    }
+
+    #[test]
+    fn test_count_pairs() {
+        let transactions = vec![vec![0, 1, 2], vec![0, 2], vec![1, 2, 3], vec![3]];
+        let item_counts: ItemCounts = hashmap! { 0 => 2, 1 => 2, 2 => 3 };
+
+        let expected = hashmap! {
+            (0, 1) => 1,
+            (0, 2) => 2,
+            (1, 2) => 2,
+        };
+        assert_eq!(count_pairs_serial(&transactions, &item_counts), expected);
+        assert_eq!(count_pairs(&transactions, &item_counts), expected);
+    }
+
+    #[test]
+    fn test_count_pairs_parallel_matches_serial() {
+        let transactions: Vec<Transaction> = (0..500)
+            .map(|i| (0..20).filter(|item| (i * 7 + item * 3) % 5 != 0).collect())
+            .collect();
+        let item_counts: ItemCounts = (0..15).map(|item| (item, 1)).collect();
+
+        assert_eq!(
+            count_pairs(&transactions, &item_counts),
+            count_pairs_serial(&transactions, &item_counts)
+        );
+    }
+
+    #[test]
+    fn test_convert_to_itemset_counts() {
+        let item_counts: ItemCounts = hashmap! {
+            13 => 3,
+            10 => 0,
+            11 => 5,
+        };
+        let itemset_counts = convert_to_itemset_counts(item_counts);
+
         let expected = hashmap! {
-            vec![10] => 0,
-            vec![11] => 5,
-            vec![13] => 3,
+            itemset![10] => 0,
+            itemset![11] => 5,
+            itemset![13] => 3,
         };
 
         assert_eq!(itemset_counts, expected);
@@ -465,13 +1623,14 @@ mod tests {
     #[test]
     fn create_counts_from_prev_1_itemset() {
         let itemset_counts = hashmap! {
-            vec![10] => 0,
-            vec![13] => 0,
-            vec![14] => 0,
+            itemset![10] => 0,
+            itemset![13] => 0,
+            itemset![14] => 0,
         };
-        let candidate_counts = generate_candidates_from_prev(&itemset_counts);
+        let candidate_counts =
+            generate_candidates_from_prev(&itemset_counts, CandidateStrategy::default());
 
-        let expected = vec![vec![10, 13], vec![10, 14], vec![13, 14]];
+        let expected = vec![itemset![10, 13], itemset![10, 14], itemset![13, 14]];
 
         assert_eq!(candidate_counts, expected);
     }
@@ -484,24 +1643,25 @@ mod tests {
             hashset![A, B, C],
             hashset![B, D],
         ];
-        let (frequent_itemsets, inventory) = generate_frequent_itemsets(transactions, 0.01, 3);
+        let (frequent_itemsets, inventory) =
+            generate_frequent_itemsets(transactions, SupportFraction(0.01), 3);
         let lookup = get_reverse_lookup(inventory);
 
         let expected = hashmap! {
             1 => hashmap! {
-                vec![lookup[A]] => 3,
-                vec![lookup[B]] => 3,
-                vec![lookup[C]] => 2,
-                vec![lookup[D]] => 1,
+                itemset![lookup[A]] => 3,
+                itemset![lookup[B]] => 3,
+                itemset![lookup[C]] => 2,
+                itemset![lookup[D]] => 1,
             },
             2 => hashmap! {
-                sorted_vec![lookup[A], lookup[B]] => 2,
-                sorted_vec![lookup[A], lookup[C]] => 2,
-                sorted_vec![lookup[B], lookup[C]] => 1,
-                sorted_vec![lookup[B], lookup[D]] => 1,
+                sorted_itemset![lookup[A], lookup[B]] => 2,
+                sorted_itemset![lookup[A], lookup[C]] => 2,
+                sorted_itemset![lookup[B], lookup[C]] => 1,
+                sorted_itemset![lookup[B], lookup[D]] => 1,
             },
             3 => hashmap! {
-                sorted_vec![0, 1, 2] => 1,
+                sorted_itemset![0, 1, 2] => 1,
             },
         };
 
@@ -516,18 +1676,19 @@ mod tests {
             hashset![A, B, C],
             hashset![B, D],
         ];
-        let (frequent_itemsets, inventory) = generate_frequent_itemsets(transactions, 0.5, 3);
+        let (frequent_itemsets, inventory) =
+            generate_frequent_itemsets(transactions, SupportFraction(0.5), 3);
         let lookup = get_reverse_lookup(inventory);
 
         let expected = hashmap! {
             1 => hashmap! {
-                vec![lookup[A]] => 3,
-                vec![lookup[B]] => 3,
-                vec![lookup[C]] => 2,
+                itemset![lookup[A]] => 3,
+                itemset![lookup[B]] => 3,
+                itemset![lookup[C]] => 2,
             },
             2 => hashmap! {
-                sorted_vec![lookup[A], lookup[B]] => 2,
-                sorted_vec![lookup[A], lookup[C]] => 2,
+                sorted_itemset![lookup[A], lookup[B]] => 2,
+                sorted_itemset![lookup[A], lookup[C]] => 2,
             },
             3 => hashmap! {},
         };
@@ -543,19 +1704,20 @@ mod tests {
             hashset![A, B, C],
             hashset![B, C],
         ];
-        let (frequent_itemsets, inventory) = generate_frequent_itemsets(transactions, 0.5, 5);
+        let (frequent_itemsets, inventory) =
+            generate_frequent_itemsets(transactions, SupportFraction(0.5), 5);
         let lookup = get_reverse_lookup(inventory);
 
         let expected = hashmap! {
             1 => hashmap! {
-                vec![lookup[A]] => 3,
-                vec![lookup[B]] => 3,
-                vec![lookup[C]] => 3,
+                itemset![lookup[A]] => 3,
+                itemset![lookup[B]] => 3,
+                itemset![lookup[C]] => 3,
             },
             2 => hashmap! {
-                sorted_vec![lookup[A], lookup[B]] => 2,
-                sorted_vec![lookup[A], lookup[C]] => 2,
-                sorted_vec![lookup[B], lookup[C]] => 2,
+                sorted_itemset![lookup[A], lookup[B]] => 2,
+                sorted_itemset![lookup[A], lookup[C]] => 2,
+                sorted_itemset![lookup[B], lookup[C]] => 2,
             },
             3 => hashmap! {},
             4 => hashmap! {},
@@ -565,7 +1727,965 @@ mod tests {
         assert_eq!(frequent_itemsets, expected);
     }
 
+    #[test]
+    fn test_candidate_strategies_agree() {
+        let items = ["a", "b", "c", "d", "e", "f", "g", "h"];
+        let raw_transactions: Vec<RawTransaction> = (0..60_usize)
+            .map(|tid| {
+                items
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| (tid * (i + 3) + i) % 7 < 4)
+                    .map(|(_, &item)| item)
+                    .collect()
+            })
+            .collect();
+        let mine = |candidate_strategy| {
+            let options = MiningOptions {
+                candidate_strategy,
+                ..MiningOptions::default()
+            };
+            let (itemsets, inventory, _) = try_generate_frequent_itemsets(
+                raw_transactions.clone(),
+                SupportFraction(0.2),
+                None,
+                &options,
+            )
+            .unwrap();
+            MiningResult::new(itemsets, inventory, raw_transactions.len()).named()
+        };
+
+        let join_only = mine(CandidateStrategy::JoinOnly);
+
+        assert!(join_only.iter().any(|(names, _)| names.len() >= 3));
+        assert_eq!(mine(CandidateStrategy::JoinAndPrune), join_only);
+    }
+
+    #[test]
+    fn test_two_phase_mine_matches_direct_mine() {
+        let items = ["a", "b", "c", "d", "e", "f"];
+        let raw_transactions: Vec<RawTransaction> = (0..50_usize)
+            .map(|tid| {
+                items
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| (tid * (i + 2) + i) % 5 < 3)
+                    .map(|(_, &item)| item)
+                    .collect()
+            })
+            .collect();
+        let N = raw_transactions.len();
+        let direct = MiningResult::mine(raw_transactions.clone(), SupportFraction(0.4), 3);
+        assert!(direct.levels[1].num_itemsets > 0);
+
+        // the whole dataset as the sample
+        let everything: Vec<usize> = (0..N).collect();
+        let (itemsets, inventory) = two_phase_mine(
+            raw_transactions.clone(),
+            &everything,
+            SupportFraction(0.2),
+            SupportFraction(0.4),
+            3,
+        );
+        let two_phase = MiningResult::new(itemsets, inventory, N);
+        assert_eq!(two_phase.levels, direct.levels);
+        assert_eq!(two_phase.named(), direct.named());
+
+        // every fifth transaction: what the sample finds is recounted exactly,
+        // and whatever it overrated is dropped
+        let sample: Vec<usize> = (0..N).step_by(5).collect();
+        let (itemsets, inventory) = two_phase_mine(
+            raw_transactions.clone(),
+            &sample,
+            SupportFraction(0.2),
+            SupportFraction(0.4),
+            3,
+        );
+        let two_phase = MiningResult::new(itemsets, inventory, N).named();
+        let direct = direct.named();
+        assert!(!two_phase.is_empty());
+        assert!(two_phase.iter().all(|itemset| direct.contains(itemset)));
+
+        let sample_transactions: Vec<RawTransaction> =
+            sample.iter().map(|&tid| raw_transactions[tid].clone()).collect();
+        let sampled = MiningResult::mine(sample_transactions, SupportFraction(0.2), 3);
+        assert!(sampled.named().len() > two_phase.len());
+    }
+
+    #[test]
+    fn test_candidate_budget_exceeded_at_level_2() {
+        let transactions = vec![
+            hashset![A, B],
+            hashset![A, C],
+            hashset![A, B, C],
+            hashset![B, D],
+        ];
+        let options = MiningOptions {
+            candidate_budget: Some(5),
+            ..MiningOptions::default()
+        };
+        let result =
+            try_generate_frequent_itemsets(transactions, SupportFraction(0.01), Some(3), &options);
+
+        assert_eq!(
+            result.unwrap_err(),
+            AprioriError::CandidateBudgetExceeded {
+                level: 2,
+                estimate: 6,
+                budget: 5,
+            }
+        );
+    }
+
+    #[cfg(feature = "deterministic-hash")]
+    #[test]
+    fn test_deterministic_hash_iterates_identically() {
+        fn run() -> (Vec<(ItemsetLength, Vec<Itemset>)>, Vec<ItemId>) {
+            let transactions = (0..50)
+                .map(|i| {
+                    let mut transaction = hashset![A, B];
+                    if i % 2 == 0 {
+                        transaction.insert(C);
+                    }
+                    if i % 3 == 0 {
+                        transaction.insert(D);
+                    }
+                    transaction
+                })
+                .collect();
+            let (itemsets, inventory) =
+                generate_frequent_itemsets(transactions, SupportFraction(0.1), 3);
+
+            let order = itemsets
+                .iter()
+                .map(|(&size, counts)| (size, counts.keys().cloned().collect()))
+                .collect();
+            (order, inventory.keys().copied().collect())
+        }
+
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_item_counts_match_level_1() {
+        let transactions = vec![
+            hashset![A, B, C],
+            hashset![A, B],
+            hashset![A, D],
+            hashset![B, C],
+        ];
+        let (itemsets, _, item_counts) = try_generate_frequent_itemsets(
+            transactions,
+            SupportFraction(0.5),
+            Some(2),
+            &MiningOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(item_counts.len(), itemsets[&1].len());
+        for (&item, &count) in &item_counts {
+            assert_eq!(itemsets[&1][&itemset![item]], count);
+        }
+    }
+
+    #[test]
+    fn test_zero_min_support() {
+        let transactions = || {
+            vec![
+                hashset![A, B, C],
+                hashset![A, D],
+                hashset![B, D],
+            ]
+        };
+
+        let options = MiningOptions::default();
+        let (itemsets, _, _) =
+            try_generate_frequent_itemsets(transactions(), SupportFraction(0.0), Some(3), &options)
+                .unwrap();
+        let (expected, _, _) = try_generate_frequent_itemsets(
+            transactions(),
+            SupportFraction(1.0 / 3.0),
+            Some(3),
+            &options,
+        )
+        .unwrap();
+
+        // only itemsets that occur, e.g. no {A, B, D}
+        assert!(itemsets.values().flat_map(|level| level.values()).all(|&count| count >= 1));
+        assert_eq!(itemsets[&3].len(), 1);
+        assert_eq!(
+            itemsets.values().map(|level| level.len()).sum::<usize>(),
+            expected.values().map(|level| level.len()).sum::<usize>()
+        );
+
+        let result =
+            try_generate_frequent_itemsets(transactions(), SupportFraction(0.0), None, &options);
+        assert_eq!(
+            result.unwrap_err(),
+            AprioriError::ThresholdTooLow { required: 0 }
+        );
+
+        // a budget bounds the enumeration instead
+        let budgeted = MiningOptions {
+            candidate_budget: Some(1000),
+            ..options.clone()
+        };
+        let (itemsets, _, _) =
+            try_generate_frequent_itemsets(transactions(), SupportFraction(0.0), None, &budgeted)
+                .unwrap();
+        assert_eq!(itemsets[&3].len(), 1);
+    }
+
+    #[test]
+    fn test_single_transaction_threshold() {
+        // 10% of 10 transactions is one: small, but not a mistake
+        let raw_transactions: Vec<RawTransaction> = (0..10)
+            .map(|tid| match tid % 3 {
+                0 => hashset![A, B],
+                1 => hashset![A, C],
+                _ => hashset![B],
+            })
+            .collect();
+
+        let (itemsets, _, _) = try_generate_frequent_itemsets(
+            raw_transactions,
+            SupportFraction(0.1),
+            None,
+            &MiningOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(itemsets[&1].len(), 3);
+        assert_eq!(itemsets[&2].len(), 2);
+        assert!(!itemsets.contains_key(&3));
+    }
+
+    #[test]
+    fn test_min_transaction_len() {
+        let transactions = || {
+            vec![
+                hashset![A, B],
+                hashset![A, B, C],
+                hashset![A],
+                hashset![C],
+            ]
+        };
+
+        // N = 4: A appears 3 times, so a 0.6 threshold needs 3 occurrences
+        let (itemsets, _, _) = try_generate_frequent_itemsets(
+            transactions(),
+            SupportFraction(0.6),
+            Some(2),
+            &MiningOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(itemsets[&1].len(), 1);
+        assert!(itemsets[&2].is_empty());
+
+        // N = 2 once the single-item baskets are gone, so 0.6 needs 2 occurrences
+        let options = MiningOptions {
+            min_transaction_len: 2,
+            ..MiningOptions::default()
+        };
+        let (itemsets, inventory, _) =
+            try_generate_frequent_itemsets(transactions(), SupportFraction(0.6), Some(2), &options)
+                .unwrap();
+        let id = |name| *inventory.iter().find(|(_, &n)| n == name).unwrap().0;
+        assert_eq!(itemsets[&1].len(), 2);
+        assert_eq!(itemsets[&1][&itemset![id(A)]], 2);
+        assert_eq!(itemsets[&2][&sorted_itemset![id(A), id(B)]], 2);
+    }
+
+    #[test]
+    fn test_max_transaction_len() {
+        let transactions = || {
+            vec![
+                hashset![A, B, C],
+                hashset![A, B, C],
+                hashset![A, B],
+                hashset![A, C],
+            ]
+        };
+        let options = MiningOptions {
+            max_transaction_len: Some(2),
+            ..MiningOptions::default()
+        };
+
+        let (itemsets, inventory, _) =
+            try_generate_frequent_itemsets(transactions(), SupportFraction(0.5), Some(3), &options)
+                .unwrap();
+        let lookup = get_reverse_lookup(inventory);
+
+        // {A, B, C} baskets keep A plus the higher-support of B and C, a tie
+        // broken by id
+        let kept = lookup[B].min(lookup[C]);
+        let dropped = lookup[B].max(lookup[C]);
+        assert_eq!(itemsets[&1][&itemset![lookup[B]]], 3);
+        assert_eq!(itemsets[&2][&sorted_itemset![lookup[A], kept]], 3);
+        assert!(!itemsets[&2].contains_key(&sorted_itemset![lookup[A], dropped]));
+        assert!(itemsets[&3].is_empty());
+    }
+
+    #[test]
+    fn test_threshold_unreachable() {
+        let transactions = vec![hashset![A, B], hashset![A, C], hashset![B, C]];
+
+        // a count passed as if it were a fraction
+        let options = MiningOptions::default();
+        let result = try_generate_frequent_itemsets(
+            transactions.clone(),
+            SupportFraction(2.0),
+            Some(2),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            AprioriError::ThresholdUnreachable {
+                required: 6,
+                n: 3
+            }
+        );
+
+        let result =
+            try_generate_frequent_itemsets(transactions, SupportFraction(1.0), Some(2), &options);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_try_generate_frequent_itemsets_id() {
+        let transactions: Vec<RawTransactionId> = vec![
+            hashset![10, 20, 30],
+            hashset![10, 20],
+            hashset![10, 30, 40],
+            hashset![20, 30, 10],
+        ];
+        let options = MiningOptions {
+            min_transaction_len: 3,
+            ..MiningOptions::default()
+        };
+
+        // the 2-item transaction is dropped, leaving 3
+        let (itemsets, item_counts) = try_generate_frequent_itemsets_id(
+            transactions.clone(),
+            SupportFraction(0.6),
+            Some(3),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(item_counts, hashmap! { 10 => 3, 20 => 2, 30 => 3 });
+        assert_eq!(
+            itemsets,
+            hashmap! {
+                1 => hashmap! { itemset![10] => 3, itemset![20] => 2, itemset![30] => 3 },
+                2 => hashmap! {
+                    itemset![10, 20] => 2,
+                    itemset![10, 30] => 3,
+                    itemset![20, 30] => 2,
+                },
+                3 => hashmap! { itemset![10, 20, 30] => 2 },
+            }
+        );
+
+        let result = try_generate_frequent_itemsets_id(
+            transactions,
+            SupportFraction(2.0),
+            Some(2),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            AprioriError::ThresholdUnreachable {
+                required: 6,
+                n: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_try_generate_frequent_itemsets_excluding() {
+        // every transaction comes in a bag, one holds nothing else
+        let transactions = vec![
+            hashset!["bag", A, B],
+            hashset!["bag", A, B],
+            hashset!["bag", A, C],
+            hashset!["bag", B],
+            hashset!["bag"],
+        ];
+        let must_exclude: HashSet<&str> = vec!["bag"].into_iter().collect();
+        let options = MiningOptions::default();
+
+        let (itemsets, inventory, _) = try_generate_frequent_itemsets_excluding(
+            transactions.clone(),
+            SupportFraction(0.5),
+            Some(3),
+            &must_exclude,
+            &options,
+        )
+        .unwrap();
+        let (full_itemsets, full_inventory, _) = try_generate_frequent_itemsets(
+            transactions.clone(),
+            SupportFraction(0.5),
+            Some(3),
+            &options,
+        )
+        .unwrap();
+
+        let excluded = MiningResult::new(itemsets, inventory, 5).named();
+        let full = MiningResult::new(full_itemsets, full_inventory, 5).named();
+        assert!(excluded.iter().all(|(names, _)| !names.contains(&"bag")));
+        // still counted against all 5 transactions, needing 3
+        let expected: Vec<(Vec<&str>, u32)> = full
+            .into_iter()
+            .filter(|(names, _)| !names.contains(&"bag"))
+            .collect();
+        assert_eq!(excluded, expected);
+        assert_eq!(excluded, vec![(vec![A], 3), (vec![B], 3)]);
+
+        // deleting the item instead drops the emptied transaction, so 2 of 4
+        // are enough
+        let deleted: Vec<RawTransaction> = transactions
+            .into_iter()
+            .map(|mut transaction| {
+                transaction.remove("bag");
+                transaction
+            })
+            .collect();
+        let options = MiningOptions {
+            min_transaction_len: 1,
+            ..MiningOptions::default()
+        };
+        let (itemsets, inventory, _) =
+            try_generate_frequent_itemsets(deleted, SupportFraction(0.5), Some(3), &options)
+                .unwrap();
+        assert_eq!(
+            MiningResult::new(itemsets, inventory, 4).named(),
+            vec![(vec![A], 3), (vec![B], 3), (vec![A, B], 2)]
+        );
+    }
+
+    #[test]
+    fn test_frequent_item_counts_only() {
+        let transactions = vec![
+            raw_transaction![A, B, C],
+            raw_transaction![A, B],
+            raw_transaction![A, D],
+            raw_transaction![E],
+        ];
+
+        let (item_counts, inventory) =
+            frequent_item_counts_only(&transactions, SupportFraction(0.5));
+        let (expected_counts, expected_inventory, _) =
+            generate_frequent_1_itemset_counts(transactions, SupportFraction(0.5));
+
+        let named = |item_counts: &ItemCounts, inventory: &Inventory<'static>| {
+            let mut named: Vec<(&str, u32)> = item_counts
+                .iter()
+                .map(|(item_id, &count)| (inventory[item_id], count))
+                .collect();
+            named.sort_unstable();
+            named
+        };
+        assert_eq!(named(&item_counts, &inventory), vec![(A, 3), (B, 2)]);
+        assert_eq!(
+            named(&item_counts, &inventory),
+            named(&expected_counts, &expected_inventory)
+        );
+        assert_eq!(inventory.len(), expected_inventory.len());
+    }
+
+    #[test]
+    fn test_count_itemsets() {
+        let transactions: Vec<Transaction> =
+            vec![vec![0, 1, 2], vec![0, 1], vec![1, 2, 3], vec![0, 2, 3]];
+
+        let counts = count_itemsets(
+            &transactions,
+            vec![
+                itemset![0, 1],
+                itemset![1, 2],
+                itemset![0, 1, 3],
+                itemset![3],
+                itemset![4],
+                itemset![],
+            ],
+        );
+
+        assert_eq!(
+            counts,
+            hashmap! {
+                itemset![0, 1] => 2,
+                itemset![1, 2] => 2,
+                itemset![0, 1, 3] => 0,
+                itemset![3] => 2,
+                itemset![4] => 0,
+                itemset![] => 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_candidate_budget_exceeded_at_level_3() {
+        let transactions = vec![hashset![A, B, C, D], hashset![A, B]];
+        let options = MiningOptions {
+            candidate_budget: Some(10),
+            ..MiningOptions::default()
+        };
+
+        // 4 frequent items give 6 candidate pairs, within budget;
+        // 6 frequent pairs could join into 15 candidates, over budget
+        let result = try_generate_frequent_itemsets(
+            transactions.clone(),
+            SupportFraction(0.5),
+            Some(2),
+            &options,
+        );
+        assert!(result.is_ok());
+
+        let result =
+            try_generate_frequent_itemsets(transactions, SupportFraction(0.5), Some(3), &options);
+        assert_eq!(
+            result.unwrap_err(),
+            AprioriError::CandidateBudgetExceeded {
+                level: 3,
+                estimate: 15,
+                budget: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn test_cancel() {
+        use std::sync::{atomic::Ordering, Arc};
+
+        let transactions = || vec![hashset![A, B, C], hashset![A, B, C], hashset![A, B]];
+        let cancel = Arc::new(AtomicBool::new(false));
+        let options = MiningOptions {
+            cancel: Some(Arc::clone(&cancel)),
+            ..MiningOptions::default()
+        };
+
+        let result =
+            try_generate_frequent_itemsets(transactions(), SupportFraction(0.5), Some(3), &options);
+        assert!(result.is_ok());
+
+        cancel.store(true, Ordering::Relaxed);
+        let result =
+            try_generate_frequent_itemsets(transactions(), SupportFraction(0.5), Some(3), &options);
+        assert_eq!(result.unwrap_err(), AprioriError::Cancelled);
+    }
+
+    #[test]
+    fn test_cancel_between_levels() {
+        use std::sync::{atomic::Ordering, Arc};
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let options = MiningOptions {
+            cancel: Some(Arc::clone(&cancel)),
+            ..MiningOptions::default()
+        };
+        let item_counts: ItemCounts = hashmap! { 0 => 2, 1 => 2, 2 => 2 };
+        let transactions = vec![vec![0, 1, 2], vec![0, 1, 2]];
+        let mut levels = LevelIter::new(item_counts, transactions, 2, Some(3), &options);
+
+        assert_eq!(levels.next().unwrap().unwrap().0, 1);
+        cancel.store(true, Ordering::Relaxed);
+        assert_eq!(levels.next().unwrap(), Err(AprioriError::Cancelled));
+        assert!(levels.next().is_none());
+    }
+
+    #[test]
+    fn test_cancel_stops_counting() {
+        let transactions = vec![vec![0, 1, 2], vec![0, 1, 2]];
+        let item_counts: ItemCounts = hashmap! { 0 => 2, 1 => 2, 2 => 2 };
+        let cancelled = AtomicBool::new(true);
+
+        assert!(count_pairs_until(&transactions, &item_counts, Some(&cancelled)).is_empty());
+        assert!(
+            count_candidates_until(vec![itemset![0, 1, 2]], &transactions, 1, Some(&cancelled))
+                .is_empty()
+        );
+        assert_eq!(
+            count_candidates_until(vec![itemset![0, 1, 2]], &transactions, 1, None).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_rounding_mode_applies_to_all_levels() {
+        // N = 4 and min_support = 0.3, so the threshold is 1.2 transactions
+        let transactions = || {
+            vec![
+                hashset![A, B],
+                hashset![A, C],
+                hashset![A, B, C],
+                hashset![B, D],
+            ]
+        };
+
+        let ceil = MiningOptions {
+            rounding: RoundingMode::Ceil,
+            ..MiningOptions::default()
+        };
+        let (frequent_itemsets, inventory, _) =
+            try_generate_frequent_itemsets(transactions(), SupportFraction(0.3), Some(2), &ceil)
+                .unwrap();
+        let lookup = get_reverse_lookup(inventory);
+        assert!(!frequent_itemsets[&1].contains_key(&itemset![lookup[D]]));
+        assert_eq!(frequent_itemsets[&2].len(), 2);
+
+        let floor = MiningOptions {
+            rounding: RoundingMode::Floor,
+            ..MiningOptions::default()
+        };
+        let (frequent_itemsets, inventory, _) =
+            try_generate_frequent_itemsets(transactions(), SupportFraction(0.3), Some(2), &floor)
+                .unwrap();
+        let lookup = get_reverse_lookup(inventory);
+        assert_eq!(frequent_itemsets[&1][&itemset![lookup[D]]], 1);
+        assert_eq!(frequent_itemsets[&2].len(), 4);
+    }
+
+    #[test]
+    fn test_level_thresholds_agree() {
+        // items 2c and 2c + 1 both occur in exactly the first c transactions,
+        // so the item 2c is frequent exactly when the pair is
+        for N in 1..=12 {
+            let raw_transactions: Vec<RawTransactionId> = (0..N)
+                .map(|t| (t + 1..=N).flat_map(|c| vec![2 * c, 2 * c + 1]).collect())
+                .collect();
+
+            let supports = (0..=100_u8).map(|i| f32::from(i) / 100.0).chain(vec![1e-9, 1e-3]);
+            for min_support in supports {
+                let (frequent_itemsets, _) = try_generate_frequent_itemsets_id(
+                    raw_transactions.clone(),
+                    SupportFraction(min_support),
+                    Some(2),
+                    &MiningOptions::default(),
+                )
+                .unwrap();
+
+                for c in 1..=N {
+                    assert_eq!(
+                        frequent_itemsets[&1].contains_key(&itemset![2 * c]),
+                        frequent_itemsets[&2].contains_key(&itemset![2 * c, 2 * c + 1]),
+                        "N = {}, min_support = {}, count = {}",
+                        N,
+                        min_support,
+                        c
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_level_min_support() {
+        // A: 4, B: 4, C: 3, D: 2; AB: 3, AC: 3, BC: 2, BD: 2; ABC: 2
+        let transactions = || {
+            vec![
+                hashset![A, B, C],
+                hashset![A, B, C],
+                hashset![A, B, D],
+                hashset![A, C],
+                hashset![B, D],
+            ]
+        };
+        let mine = |level_min_support: Vec<f32>| {
+            let options = MiningOptions {
+                level_min_support: level_min_support.into_iter().map(SupportFraction).collect(),
+                ..MiningOptions::default()
+            };
+            let (frequent_itemsets, inventory, _) = try_generate_frequent_itemsets(
+                transactions(),
+                SupportFraction(0.4),
+                Some(3),
+                &options,
+            )
+            .unwrap();
+            let mut levels: Vec<Vec<Vec<&str>>> = vec![];
+            for size in 1..=3 {
+                let mut level: Vec<Vec<&str>> = frequent_itemsets[&size]
+                    .keys()
+                    .map(|itemset| {
+                        let mut names: Vec<&str> =
+                            itemset.iter().map(|id| inventory[id]).collect();
+                        names.sort_unstable();
+                        names
+                    })
+                    .collect();
+                level.sort();
+                levels.push(level);
+            }
+            levels
+        };
+
+        // stricter for single items, then the default for the rest
+        assert_eq!(
+            mine(vec![0.6]),
+            vec![
+                vec![vec![A], vec![B], vec![C]],
+                vec![vec![A, B], vec![A, C], vec![B, C]],
+                vec![vec![A, B, C]],
+            ]
+        );
+
+        // stricter as itemsets grow
+        assert_eq!(
+            mine(vec![0.2, 0.4, 0.6]),
+            vec![
+                vec![vec![A], vec![B], vec![C], vec![D]],
+                vec![vec![A, B], vec![A, C], vec![B, C], vec![B, D]],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_levels() {
+        let transactions = || vec![hashset![A, B], hashset![A, B], hashset![A, C]];
+        let mine = |empty_levels| {
+            let options = MiningOptions {
+                empty_levels,
+                ..MiningOptions::default()
+            };
+            let (frequent_itemsets, _, _) = try_generate_frequent_itemsets(
+                transactions(),
+                SupportFraction(0.5),
+                Some(4),
+                &options,
+            )
+            .unwrap();
+            let mut sizes: Vec<ItemsetLength> = frequent_itemsets.keys().copied().collect();
+            sizes.sort_unstable();
+            sizes
+        };
+
+        assert_eq!(mine(EmptyLevels::default()), vec![1, 2, 3, 4]);
+        assert_eq!(mine(EmptyLevels::Include), vec![1, 2, 3, 4]);
+        assert_eq!(mine(EmptyLevels::Omit), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_levels_iter_matches_batch() {
+        let transactions = || {
+            vec![
+                hashset![A, B, C, D],
+                hashset![A, B, C],
+                hashset![A, B, D],
+                hashset![A, C, E],
+                hashset![B, C, D],
+            ]
+        };
+
+        // ids depend on hashing order, so compare by name
+        let named = |frequent_itemsets: &FrequentItemsets, inventory: &Inventory<'static>| {
+            let mut named: Vec<(ItemsetLength, Vec<&str>, u32)> = frequent_itemsets
+                .iter()
+                .flat_map(|(&size, itemset_counts)| {
+                    itemset_counts
+                        .iter()
+                        .map(move |(itemset, &count)| (size, itemset, count))
+                })
+                .map(|(size, itemset, count)| {
+                    let mut names: Vec<&str> = itemset.iter().map(|id| inventory[id]).collect();
+                    names.sort_unstable();
+                    (size, names, count)
+                })
+                .collect();
+            named.sort_unstable();
+            named
+        };
+
+        for &k in &[1, 2, 3, 5] {
+            let (expected, inventory) =
+                generate_frequent_itemsets(transactions(), SupportFraction(0.4), k);
+
+            let mut levels = levels_iter(transactions(), SupportFraction(0.4), k);
+            let mut sizes = vec![];
+            let mut frequent_itemsets: FrequentItemsets = HashMap::default();
+            for (size, itemset_counts) in levels.by_ref() {
+                sizes.push(size);
+                frequent_itemsets.insert(size, itemset_counts);
+            }
+
+            assert_eq!(sizes, (1..=k).collect::<Vec<_>>());
+            assert_eq!(
+                named(&frequent_itemsets, levels.inventory()),
+                named(&expected, &inventory)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mine_to_channel() {
+        use std::sync::mpsc;
+        use std::thread;
+
+        let transactions = || {
+            vec![
+                hashset![A, B, C, D],
+                hashset![A, B, C],
+                hashset![A, B, D],
+                hashset![A, C, D],
+                hashset![B, E],
+            ]
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let consumer = thread::spawn(move || rx.iter().collect::<Vec<_>>());
+        let inventory = mine_to_channel(transactions(), SupportFraction(0.4), 4, tx);
+        let levels = consumer.join().unwrap();
+
+        let sizes: Vec<ItemsetLength> = levels.iter().map(|&(size, _)| size).collect();
+        assert_eq!(sizes, vec![1, 2, 3, 4]);
+
+        // ids depend on hashing order, so compare by name
+        let named = |itemsets: FrequentItemsets, inventory: &Inventory<'static>| {
+            let mut named: Vec<(Vec<&str>, u32)> = itemsets
+                .into_values()
+                .flatten()
+                .map(|(itemset, count)| {
+                    let mut names: Vec<&str> = itemset.iter().map(|id| inventory[id]).collect();
+                    names.sort_unstable();
+                    (names, count)
+                })
+                .collect();
+            named.sort_unstable();
+            named
+        };
+        let (expected, expected_inventory) =
+            generate_frequent_itemsets(transactions(), SupportFraction(0.4), 4);
+        assert_eq!(
+            named(levels.into_iter().collect(), &inventory),
+            named(expected, &expected_inventory)
+        );
+    }
+
+    #[test]
+    fn test_generate_frequent_itemsets_within() {
+        use crate::itemsets::query::count_all_itemsets;
+
+        let transactions = || {
+            vec![
+                hashset![A, B, C, D],
+                hashset![A, B, C],
+                hashset![A, B, D],
+                hashset![A, C, D],
+            ]
+        };
+
+        let result = generate_frequent_itemsets_within(
+            transactions(),
+            SupportFraction(0.5),
+            4,
+            Duration::ZERO,
+        );
+        assert!(result.timed_out);
+        assert_eq!(result.itemsets.keys().copied().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(result.itemsets[&1].len(), 4);
+
+        let (expected, _) = generate_frequent_itemsets(transactions(), SupportFraction(0.5), 4);
+        let result = generate_frequent_itemsets_within(
+            transactions(),
+            SupportFraction(0.5),
+            4,
+            Duration::from_secs(3600),
+        );
+        assert!(!result.timed_out);
+        assert_eq!(
+            count_all_itemsets(&result.itemsets),
+            count_all_itemsets(&expected)
+        );
+        assert_eq!(result.itemsets.len(), 4);
+    }
+
+    #[test]
+    fn test_generate_frequent_itemsets_open_ended() {
+        let transactions = vec![
+            hashset![A, B, C],
+            hashset![A, B, C],
+            hashset![A, B, D],
+            hashset![C, D],
+        ];
+        let (frequent_itemsets, inventory, _) = try_generate_frequent_itemsets(
+            transactions,
+            SupportFraction(0.5),
+            None,
+            &MiningOptions::default(),
+        )
+        .unwrap();
+        let lookup = get_reverse_lookup(inventory);
+
+        let expected = hashmap! {
+            1 => hashmap! {
+                itemset![lookup[A]] => 3,
+                itemset![lookup[B]] => 3,
+                itemset![lookup[C]] => 3,
+                itemset![lookup[D]] => 2,
+            },
+            2 => hashmap! {
+                sorted_itemset![lookup[A], lookup[B]] => 3,
+                sorted_itemset![lookup[A], lookup[C]] => 2,
+                sorted_itemset![lookup[B], lookup[C]] => 2,
+            },
+            3 => hashmap! {
+                sorted_itemset![lookup[A], lookup[B], lookup[C]] => 2,
+            },
+        };
+
+        assert_eq!(frequent_itemsets, expected);
+    }
+
+    #[test]
+    fn test_generate_frequent_itemsets_open_ended_no_pairs() {
+        let transactions = vec![hashset![A], hashset![B], hashset![A, B]];
+        let (frequent_itemsets, _, _) = try_generate_frequent_itemsets(
+            transactions,
+            SupportFraction(0.6),
+            None,
+            &MiningOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(frequent_itemsets.len(), 1);
+        assert_eq!(frequent_itemsets[&1].len(), 2);
+    }
+
     fn get_reverse_lookup(inventory: Inventory) -> ReverseLookup {
         inventory.into_iter().map(|(k, v)| (v, k)).collect()
     }
+
+    #[test]
+    fn test_large_n_threshold_rounds_up() {
+        // 0.4 * 10_000_001 is 4_000_000.4, so 4_000_001 transactions are
+        // needed; the f32 error of 0.4 alone is worth almost half a
+        // transaction here and must not round the threshold down
+        let N = 10_000_001;
+        let raw_transactions: Vec<RawTransaction> = (0..N)
+            .map(|i| {
+                let mut raw_transaction = RawTransaction::default();
+                if i < 4_000_000 {
+                    raw_transaction.insert("a");
+                }
+                if i <= 4_000_000 {
+                    raw_transaction.insert("b");
+                }
+                raw_transaction
+            })
+            .collect();
+
+        let (itemsets, inventory, _) = try_generate_frequent_itemsets(
+            raw_transactions,
+            SupportFraction(0.4),
+            Some(1),
+            &MiningOptions::default(),
+        )
+        .unwrap();
+
+        let frequent: Vec<(&str, u32)> = itemsets[&1]
+            .iter()
+            .map(|(itemset, &count)| (inventory[&itemset[0]], count))
+            .collect();
+        assert_eq!(frequent, vec![("b", 4_000_001)]);
+    }
 }
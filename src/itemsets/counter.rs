@@ -0,0 +1,116 @@
+use crate::{
+    itemsets::{
+        count::mine_levels,
+        encode::Encoder,
+        options::{MiningOptions, RoundingMode},
+        support::SupportFraction,
+    },
+    types::{FrequentItemsets, Inventory, ItemsetLength, RawTransaction, Transaction},
+};
+
+/// Push-style counterpart to
+/// [`generate_frequent_itemsets`](crate::itemsets::count::generate_frequent_itemsets),
+/// for transactions that arrive one at a time from a stream or cursor.
+///
+/// Each pushed transaction is encoded and counted straight away, so only the
+/// encoded form is kept; the raw transactions need never be collected.
+/// Mining itself waits for [`finalize`](Counter::finalize), as no itemset can
+/// be ruled out before N is known.
+#[derive(Debug, Default)]
+pub struct Counter<'l> {
+    encoder: Encoder<'l>,
+    transactions: Vec<Transaction>,
+}
+
+impl<'l> Counter<'l> {
+    /// An empty counter.
+    ///
+    /// There is no `min_support` hint to give up front: a fraction of an N
+    /// that is still growing sets no count an item must reach, and an item
+    /// rare in the transactions pushed so far can still turn frequent in the
+    /// ones to come. So every encoded transaction is kept whole, and the
+    /// threshold is only applied by [`finalize`](Counter::finalize).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encode and count one transaction.
+    pub fn push(&mut self, raw_transaction: RawTransaction<'l>) {
+        let transaction = self.encoder.encode(&raw_transaction);
+        self.transactions.push(transaction);
+    }
+
+    /// Number of transactions pushed so far, i.e. N.
+    pub fn num_transactions(&self) -> usize {
+        self.encoder.num_transactions()
+    }
+
+    /// Mine everything pushed so far, with the same result as passing the
+    /// transactions in the same order to the batch API.
+    pub fn finalize(
+        self,
+        min_support: SupportFraction,
+        k: ItemsetLength,
+    ) -> (FrequentItemsets, Inventory<'l>) {
+        let min_support_count =
+            min_support.support_count(self.num_transactions(), RoundingMode::default());
+        let (mut item_counts, inventory) = self.encoder.finish();
+        item_counts.retain(|_, &mut support_count| support_count as usize >= min_support_count);
+
+        let all_frequent_itemsets = mine_levels(
+            item_counts,
+            self.transactions,
+            min_support_count,
+            Some(k),
+            &MiningOptions::default(),
+        )
+        .expect("default options impose no limits");
+
+        (all_frequent_itemsets, inventory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::count::generate_frequent_itemsets;
+
+    #[test]
+    fn test_push_then_finalize_matches_batch() {
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["bread", "milk", "cheese"],
+            vec!["bread", "milk"],
+            vec!["milk", "cheese", "bread"],
+            vec!["milk", "cheese", "yoghurt"],
+            vec!["bread", "jam"],
+            vec!["milk", "bread", "yoghurt"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        for &(min_support, k) in &[(0.3, 3), (0.5, 2), (0.9, 3)] {
+            let mut counter = Counter::new();
+            for raw_transaction in raw_transactions.clone() {
+                counter.push(raw_transaction);
+            }
+            assert_eq!(counter.num_transactions(), 6);
+
+            assert_eq!(
+                counter.finalize(SupportFraction(min_support), k),
+                generate_frequent_itemsets(
+                    raw_transactions.clone(),
+                    SupportFraction(min_support),
+                    k
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_finalize_empty() {
+        let (itemsets, inventory) = Counter::new().finalize(SupportFraction(0.5), 2);
+        assert!(itemsets.values().all(|level| level.is_empty()));
+        assert!(inventory.is_empty());
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{
+    itemsets::count::{APPROX_NUM_ITEMS_IN_1_TRANSACTION, APPROX_NUM_UNIQUE_ITEMS},
+    types::{
+        HashState, Inventory, ItemCounts, ItemId, ItemName, RawTransaction, ReverseLookup,
+        Transaction,
+    },
+};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+/// Assign an id to every distinct item, without counting anything.
+///
+/// Ids follow the sorted item names, so they depend only on which items occur,
+/// not on the order of transactions or of items within them. Returns the
+/// id-to-name and name-to-id maps, which are exact inverses of each other.
+/// Pass the latter to [`Encoder::seeded`] to encode with these ids.
+pub fn build_vocabulary<'l>(
+    raw_transactions: &[RawTransaction<'l>],
+) -> (Inventory<'l>, ReverseLookup<'l>) {
+    vocabulary_of(raw_transactions.iter())
+}
+
+/// [`build_vocabulary`] over transactions that are not in one slice.
+pub(crate) fn vocabulary_of<'a, 'l: 'a>(
+    raw_transactions: impl Iterator<Item = &'a RawTransaction<'l>>,
+) -> (Inventory<'l>, ReverseLookup<'l>) {
+    let mut items: Vec<ItemName<'l>> = raw_transactions
+        .flat_map(|raw_transaction| raw_transaction.iter().copied())
+        .collect();
+    items.sort_unstable();
+    items.dedup();
+
+    let inventory: Inventory = items.iter().copied().enumerate().collect();
+    let reverse_lookup: ReverseLookup = inventory.iter().map(|(&id, &name)| (name, id)).collect();
+
+    (inventory, reverse_lookup)
+}
+
+/// Incremental first pass: turns raw transactions into item ids one at a time,
+/// counting every item along the way.
+///
+/// Feeding transactions straight from their source means the raw and encoded
+/// forms of the whole dataset never have to be held at the same time; combine
+/// with [`encode_to_file`](crate::itemsets::source::encode_to_file) to keep the
+/// encoded form out of memory too.
+#[derive(Debug)]
+pub struct Encoder<'l> {
+    reverse_lookup: ReverseLookup<'l>,
+    inventory: Inventory<'l>,
+    item_counts: ItemCounts,
+    next_item_id: ItemId,
+    num_transactions: usize,
+    items: Vec<ItemId>,
+}
+
+impl<'l> Encoder<'l> {
+    pub fn new() -> Self {
+        Self::seeded(&ReverseLookup::default())
+    }
+
+    /// Start from a fixed item-to-id mapping. Seeded items keep their ids and
+    /// new items get fresh ids after the largest seeded id.
+    pub fn seeded(seed: &ReverseLookup<'l>) -> Self {
+        let mut reverse_lookup: ReverseLookup =
+            HashMap::with_capacity_and_hasher(APPROX_NUM_UNIQUE_ITEMS, Default::default());
+        let mut inventory: Inventory =
+            HashMap::with_capacity_and_hasher(APPROX_NUM_UNIQUE_ITEMS, Default::default());
+        reverse_lookup.extend(seed);
+        inventory.extend(seed.iter().map(|(&item, &item_id)| (item_id, item)));
+
+        Self {
+            reverse_lookup,
+            inventory,
+            item_counts: HashMap::with_capacity_and_hasher(
+                APPROX_NUM_UNIQUE_ITEMS,
+                Default::default(),
+            ),
+            next_item_id: seed.values().max().map_or(0, |max_id| max_id + 1),
+            num_transactions: 0,
+            items: Vec::with_capacity(APPROX_NUM_ITEMS_IN_1_TRANSACTION),
+        }
+    }
+
+    /// Encode one transaction, assigning ids to items seen for the first time.
+    pub fn encode(&mut self, raw_transaction: &RawTransaction<'l>) -> Transaction {
+        self.items.clear();
+
+        for &item in raw_transaction {
+            let item_id = self.count_item(item);
+            self.items.push(item_id);
+        }
+        self.num_transactions += 1;
+
+        self.items.sort_unstable();
+        self.items.to_owned()
+    }
+
+    /// Count one transaction as [`encode`](Self::encode) does, without
+    /// building its encoding.
+    pub fn count(&mut self, raw_transaction: &RawTransaction<'l>) {
+        for &item in raw_transaction {
+            self.count_item(item);
+        }
+        self.num_transactions += 1;
+    }
+
+    fn count_item(&mut self, item: ItemName<'l>) -> ItemId {
+        let item_id = match self.reverse_lookup.get(item) {
+            Some(&item_id) => item_id,
+            None => {
+                let item_id = self.next_item_id;
+                self.reverse_lookup.insert(item, item_id);
+                self.inventory.insert(item_id, item);
+                self.next_item_id += 1;
+                item_id
+            }
+        };
+        *self.item_counts.entry(item_id).or_insert(0) += 1;
+        item_id
+    }
+
+    /// Number of transactions encoded so far, i.e. N.
+    pub fn num_transactions(&self) -> usize {
+        self.num_transactions
+    }
+
+    /// Unpruned counts of every item seen, and the id-to-name inventory.
+    pub fn finish(self) -> (ItemCounts, Inventory<'l>) {
+        (self.item_counts, self.inventory)
+    }
+}
+
+impl<'l> Default for Encoder<'l> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Everything the first pass produces: unpruned item counts, the id-to-name
+/// inventory and the encoded transactions.
+pub type Encoding<'l> = (ItemCounts, Inventory<'l>, Vec<Transaction>);
+
+/// Hash of the contents of `raw_transactions` that does not depend on the
+/// iteration order of the items within a transaction, only on which items each
+/// one holds and on the order of the transactions.
+///
+/// The hasher has fixed keys, so the same data hashes the same on every call
+/// and in every process built from the same toolchain.
+pub fn content_hash(raw_transactions: &[RawTransaction]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let mut items: Vec<ItemName> = Vec::with_capacity(APPROX_NUM_ITEMS_IN_1_TRANSACTION);
+
+    raw_transactions.len().hash(&mut hasher);
+    for raw_transaction in raw_transactions {
+        items.clear();
+        items.extend(raw_transaction.iter().copied());
+        items.sort_unstable();
+        items.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Memoised first pass, for sessions that mine the same data again and again.
+///
+/// Encodings are keyed by [`content_hash`], so handing over a dataset that was
+/// encoded before, even as a different but equal `Vec`, returns the stored
+/// encoding instead of encoding it again. Only the hash is compared; two
+/// different datasets whose hashes collide would share an encoding.
+#[derive(Debug, Default)]
+pub struct EncodingCache<'l> {
+    encodings: HashMap<u64, Encoding<'l>, HashState>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<'l> EncodingCache<'l> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The encoding of `raw_transactions`, from the cache if it holds one.
+    pub fn encode(&mut self, raw_transactions: &[RawTransaction<'l>]) -> &Encoding<'l> {
+        let key = content_hash(raw_transactions);
+        if self.encodings.contains_key(&key) {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+
+        self.encodings.entry(key).or_insert_with(|| {
+            let mut encoder = Encoder::new();
+            let transactions = raw_transactions
+                .iter()
+                .map(|raw_transaction| encoder.encode(raw_transaction))
+                .collect();
+            let (item_counts, inventory) = encoder.finish();
+            (item_counts, inventory, transactions)
+        })
+    }
+
+    /// Number of calls to [`encode`](Self::encode) answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of calls to [`encode`](Self::encode) that had to encode.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Number of distinct datasets held.
+    pub fn len(&self) -> usize {
+        self.encodings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.encodings.is_empty()
+    }
+
+    /// Drop every stored encoding.
+    pub fn clear(&mut self) {
+        self.encodings.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::count::count_items;
+
+    #[test]
+    fn test_encoder_matches_batch_encoding() {
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["a", "b", "c"],
+            vec!["b", "c"],
+            vec!["c", "d"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        let mut encoder = Encoder::new();
+        let transactions: Vec<Transaction> = raw_transactions
+            .iter()
+            .map(|raw_transaction| encoder.encode(raw_transaction))
+            .collect();
+        assert_eq!(encoder.num_transactions(), 3);
+        let (item_counts, inventory) = encoder.finish();
+
+        assert_eq!(
+            (item_counts, inventory, transactions),
+            count_items(raw_transactions)
+        );
+    }
+
+    #[test]
+    fn test_seeded_encoder_keeps_ids() {
+        let seed: ReverseLookup = hashmap! { "z" => 7 };
+        let mut encoder = Encoder::seeded(&seed);
+
+        let transaction = encoder.encode(&vec!["z", "y"].into_iter().collect());
+
+        assert_eq!(transaction, vec![7, 8]);
+        let (_, inventory) = encoder.finish();
+        assert_eq!(inventory[&8], "y");
+    }
+
+    #[test]
+    fn test_build_vocabulary() {
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["milk", "bread"],
+            vec!["cheese", "milk"],
+            vec!["bread"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        let (inventory, reverse_lookup) = build_vocabulary(&raw_transactions);
+
+        assert_eq!(inventory, hashmap! { 0 => "bread", 1 => "cheese", 2 => "milk" });
+        assert_eq!(reverse_lookup.len(), inventory.len());
+        for (id, name) in &inventory {
+            assert_eq!(reverse_lookup[name], *id);
+        }
+
+        let mut encoder = Encoder::seeded(&reverse_lookup);
+        assert_eq!(encoder.encode(&raw_transactions[1]), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_encoding_cache() {
+        let raw_transactions = |order: [&'static str; 2]| -> Vec<RawTransaction<'static>> {
+            vec![order.iter().copied().collect(), vec!["c"].into_iter().collect()]
+        };
+        let mut cache = EncodingCache::new();
+
+        let first = cache.encode(&raw_transactions(["a", "b"])).clone();
+        assert_eq!((cache.hits(), cache.misses()), (0, 1));
+
+        // equal contents, built separately
+        let second = cache.encode(&raw_transactions(["b", "a"])).clone();
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        assert_eq!(first, second);
+        assert_eq!(first.2, vec![vec![0, 1], vec![2]]);
+
+        cache.encode(&raw_transactions(["a", "c"]));
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["a", "b"].into_iter().collect(),
+            vec!["c"].into_iter().collect(),
+        ];
+        let mut reversed = raw_transactions.clone();
+        reversed.reverse();
+
+        assert_eq!(
+            content_hash(&raw_transactions),
+            content_hash(&raw_transactions.clone())
+        );
+        assert_ne!(content_hash(&raw_transactions), content_hash(&reversed));
+        assert_ne!(content_hash(&raw_transactions), content_hash(&[]));
+    }
+}
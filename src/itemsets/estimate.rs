@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use crate::{
+    itemsets::{options::RoundingMode, search::estimate_candidate_count, support::SupportFraction},
+    types::{ItemId, ItemName, Itemset, ItemsetLength, RawTransaction, Transaction},
+};
+
+/// Rough projection of what a mining run will hold in memory.
+/// See [`estimate_memory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// Distinct items across all transactions.
+    pub num_items: usize,
+    /// Items meeting the support threshold, the only ones that survive encoding.
+    pub num_frequent_items: usize,
+    /// The encoded transactions, pruned to frequent items.
+    pub transaction_bytes: usize,
+    /// The inventory and reverse lookup.
+    pub vocabulary_bytes: usize,
+    /// Upper bound on the candidates counted at each level, starting with
+    /// level 1 at index 0.
+    pub candidates_per_level: Vec<usize>,
+    /// Largest projected total over all levels: transactions, vocabulary, the
+    /// itemsets kept so far and the current level's candidates.
+    pub peak_bytes: usize,
+}
+
+/// Project peak memory for mining `raw_transactions` at `min_support` up to
+/// `k` items, without mining them.
+///
+/// This is an order-of-magnitude planning aid, not a measurement. It takes one
+/// pass to count items, then bounds each level from both sides: candidates
+/// cannot outnumber what the join step can make from the previous level, nor
+/// the `k`-subsets of the frequent items, and frequent itemsets cannot
+/// outnumber the `k`-subsets the transactions hold divided by the support
+/// count. Every candidate is assumed frequent, so real runs usually come in
+/// well under the estimate; if it is still affordable, the run is too.
+/// Allocator and hash table overheads are ignored.
+pub fn estimate_memory(
+    raw_transactions: &[RawTransaction],
+    min_support: SupportFraction,
+    k: ItemsetLength,
+) -> MemoryEstimate {
+    let min_support_count = min_support
+        .support_count(raw_transactions.len(), RoundingMode::default())
+        .max(1);
+
+    let mut item_counts: HashMap<ItemName, usize> = HashMap::new();
+    for transaction in raw_transactions {
+        for &item in transaction {
+            *item_counts.entry(item).or_insert(0) += 1;
+        }
+    }
+    let num_items = item_counts.len();
+    let num_frequent_items = item_counts
+        .values()
+        .filter(|&&count| count >= min_support_count)
+        .count();
+
+    let lengths: Vec<usize> = raw_transactions
+        .iter()
+        .map(|transaction| {
+            transaction
+                .iter()
+                .filter(|item| item_counts[*item] >= min_support_count)
+                .count()
+        })
+        .collect();
+    let transaction_bytes = lengths.iter().fold(0_usize, |bytes, &len| {
+        bytes.saturating_add(size_of::<Transaction>() + len * size_of::<ItemId>())
+    });
+    let vocabulary_bytes = num_items * 2 * (size_of::<ItemId>() + size_of::<ItemName>());
+
+    let mut candidates_per_level = vec![];
+    let mut kept_bytes = 0_usize;
+    let mut peak_bytes = transaction_bytes.saturating_add(vocabulary_bytes);
+    let mut prev_frequent = num_frequent_items;
+
+    for size in 1..=k {
+        let candidates = if size == 1 {
+            num_items
+        } else {
+            estimate_candidate_count(prev_frequent).min(binomial(num_frequent_items, size))
+        };
+        if candidates == 0 {
+            break;
+        }
+        let occurrences = lengths.iter().fold(0_usize, |total, &len| {
+            total.saturating_add(binomial(len, size))
+        });
+        let frequent = candidates.min(occurrences / min_support_count);
+
+        let candidate_bytes = candidates.saturating_mul(itemset_bytes(size));
+        peak_bytes = peak_bytes.max(
+            transaction_bytes
+                .saturating_add(vocabulary_bytes)
+                .saturating_add(kept_bytes)
+                .saturating_add(candidate_bytes),
+        );
+        kept_bytes = kept_bytes.saturating_add(frequent.saturating_mul(itemset_bytes(size)));
+
+        candidates_per_level.push(candidates);
+        prev_frequent = if size == 1 {
+            num_frequent_items
+        } else {
+            frequent
+        };
+    }
+
+    MemoryEstimate {
+        num_items,
+        num_frequent_items,
+        transaction_bytes,
+        vocabulary_bytes,
+        candidates_per_level,
+        peak_bytes,
+    }
+}
+
+/// An itemset of `size` items with its count.
+fn itemset_bytes(size: ItemsetLength) -> usize {
+    size_of::<Itemset>() + size * size_of::<ItemId>() + size_of::<u32>()
+}
+
+/// `n` choose `k`, saturating at `usize::MAX`.
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    let mut result: u128 = 1;
+    for i in 0..k {
+        result = result * (n - i) as u128 / (i + 1) as u128;
+        if result > usize::MAX as u128 {
+            return usize::MAX;
+        }
+    }
+    result as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transactions() -> Vec<RawTransaction<'static>> {
+        vec![
+            vec!["bread", "milk", "cheese"],
+            vec!["bread", "milk"],
+            vec!["milk", "cheese", "bread"],
+            vec!["milk", "cheese", "yoghurt"],
+            vec!["bread", "jam"],
+            vec!["milk", "bread", "yoghurt"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect()
+    }
+
+    #[test]
+    fn test_estimate_memory() {
+        let estimate = estimate_memory(&transactions(), SupportFraction(0.3), 3);
+
+        assert_eq!(estimate.num_items, 5);
+        // bread, milk, cheese and yoghurt
+        assert_eq!(estimate.num_frequent_items, 4);
+        // 15 frequent item occurrences
+        assert_eq!(
+            estimate.transaction_bytes,
+            6 * size_of::<Transaction>() + 15 * size_of::<ItemId>()
+        );
+        // all 5 items, then every pair of the 4 frequent ones, then at most 4 triples
+        assert_eq!(estimate.candidates_per_level[..2], [5, 6]);
+        assert!(estimate.candidates_per_level[2] <= 4);
+        assert!(estimate.peak_bytes > estimate.transaction_bytes + estimate.vocabulary_bytes);
+        assert!(estimate.peak_bytes < 1 << 20);
+    }
+
+    #[test]
+    fn test_estimate_memory_grows_as_threshold_drops() {
+        let strict = estimate_memory(&transactions(), SupportFraction(0.8), 3);
+        let loose = estimate_memory(&transactions(), SupportFraction(0.3), 3);
+
+        // only bread and milk, so a single pair and no triples
+        assert_eq!(strict.num_frequent_items, 2);
+        assert_eq!(strict.candidates_per_level, vec![5, 1]);
+        assert!(loose.peak_bytes > strict.peak_bytes);
+    }
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(binomial(4, 2), 6);
+        assert_eq!(binomial(10, 3), 120);
+        assert_eq!(binomial(2, 3), 0);
+        assert_eq!(binomial(1000, 500), usize::MAX);
+    }
+}
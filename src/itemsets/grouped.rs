@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    itemsets::{
+        count::{
+            generate_frequent_1_itemset_counts_seeded, generate_frequent_itemsets, mine_levels,
+        },
+        encode::vocabulary_of,
+        options::{MiningOptions, RoundingMode},
+        support::SupportFraction,
+    },
+    types::{FrequentItemsets, Inventory, ItemsetLength, RawTransaction},
+};
+
+/// Mine every group of transactions separately, e.g. one group per store region.
+///
+/// Transactions are partitioned by their key, then each group is mined on its
+/// own: supports are relative to the size of the group, and every group gets
+/// its own inventory. With the `parallel` feature, groups are mined
+/// concurrently.
+pub fn generate_frequent_itemsets_grouped<'l, K>(
+    tagged_transactions: Vec<(K, RawTransaction<'l>)>,
+    min_support: SupportFraction,
+    k: ItemsetLength,
+) -> HashMap<K, (FrequentItemsets, Inventory<'l>)>
+where
+    K: Eq + Hash + Send,
+{
+    let mut groups: HashMap<K, Vec<RawTransaction<'l>>> = HashMap::new();
+    for (key, raw_transaction) in tagged_transactions {
+        groups.entry(key).or_default().push(raw_transaction);
+    }
+
+    #[cfg(feature = "parallel")]
+    let groups = groups.into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let groups = groups.into_iter();
+
+    groups
+        .map(|(key, raw_transactions)| {
+            (
+                key,
+                generate_frequent_itemsets(raw_transactions, min_support, k),
+            )
+        })
+        .collect()
+}
+
+/// Same as [`generate_frequent_itemsets_grouped`], with all groups sharing one
+/// inventory, so that an itemset has the same ids in every group.
+///
+/// The inventory is built once from every transaction, ids following the
+/// sorted item names as in [`build_vocabulary`](crate::itemsets::encode::build_vocabulary),
+/// and each group is then encoded against it. Results of different groups can
+/// be compared key for key, e.g. with
+/// [`diff_itemsets`](crate::itemsets::compare::diff_itemsets), without merging
+/// inventories. Supports are still relative to the size of each group.
+pub fn generate_frequent_itemsets_grouped_shared<'l, K>(
+    tagged_transactions: Vec<(K, RawTransaction<'l>)>,
+    min_support: SupportFraction,
+    k: ItemsetLength,
+) -> (HashMap<K, FrequentItemsets>, Inventory<'l>)
+where
+    K: Eq + Hash + Send,
+{
+    let mut groups: HashMap<K, Vec<RawTransaction<'l>>> = HashMap::new();
+    for (key, raw_transaction) in tagged_transactions {
+        groups.entry(key).or_default().push(raw_transaction);
+    }
+    let (inventory, reverse_lookup) = vocabulary_of(groups.values().flatten());
+
+    #[cfg(feature = "parallel")]
+    let groups = groups.into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let groups = groups.into_iter();
+
+    let itemsets = groups
+        .map(|(key, raw_transactions)| {
+            let min_support_count =
+                min_support.support_count(raw_transactions.len(), RoundingMode::default());
+            let (item_counts, _, transactions) = generate_frequent_1_itemset_counts_seeded(
+                raw_transactions,
+                min_support,
+                &reverse_lookup,
+            );
+            let itemsets = mine_levels(
+                item_counts,
+                transactions,
+                min_support_count,
+                Some(k),
+                &MiningOptions::default(),
+            )
+            .expect("default options impose no limits");
+            (key, itemsets)
+        })
+        .collect();
+
+    (itemsets, inventory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(
+        itemsets: &FrequentItemsets,
+        inventory: &Inventory,
+        size: ItemsetLength,
+    ) -> Vec<Vec<String>> {
+        let mut names: Vec<Vec<String>> = itemsets[&size]
+            .keys()
+            .map(|itemset| {
+                let mut names: Vec<String> =
+                    itemset.iter().map(|id| inventory[id].to_string()).collect();
+                names.sort();
+                names
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_grouped() {
+        let tagged_transactions = vec![
+            ("north", vec!["bread", "milk"]),
+            ("north", vec!["bread", "milk", "eggs"]),
+            ("north", vec!["bread"]),
+            ("south", vec!["rice", "beans"]),
+            ("south", vec!["rice", "beans", "milk"]),
+        ]
+        .into_iter()
+        .map(|(region, items)| (region, items.into_iter().collect()))
+        .collect();
+
+        let grouped =
+            generate_frequent_itemsets_grouped(tagged_transactions, SupportFraction(0.6), 2);
+
+        assert_eq!(grouped.len(), 2);
+        let (itemsets, inventory) = &grouped["north"];
+        assert_eq!(
+            names(itemsets, inventory, 1),
+            vec![vec!["bread"], vec!["milk"]]
+        );
+        assert_eq!(names(itemsets, inventory, 2), vec![vec!["bread", "milk"]]);
+
+        // 2 of 5 transactions overall, but both of the south's
+        let (itemsets, inventory) = &grouped["south"];
+        assert_eq!(
+            names(itemsets, inventory, 1),
+            vec![vec!["beans"], vec!["rice"]]
+        );
+        assert_eq!(names(itemsets, inventory, 2), vec![vec!["beans", "rice"]]);
+    }
+
+    #[test]
+    fn test_grouped_shared_inventory() {
+        let tagged_transactions = vec![
+            ("north", vec!["bread", "milk"]),
+            ("north", vec!["bread", "milk", "eggs"]),
+            ("north", vec!["bread"]),
+            ("south", vec!["rice", "milk", "bread"]),
+            ("south", vec!["rice", "milk"]),
+        ]
+        .into_iter()
+        .map(|(region, items)| (region, items.into_iter().collect()))
+        .collect();
+
+        let (grouped, inventory) =
+            generate_frequent_itemsets_grouped_shared(tagged_transactions, SupportFraction(0.6), 2);
+
+        // ids follow the sorted names of all items
+        assert_eq!(
+            inventory,
+            hashmap! { 0 => "bread", 1 => "eggs", 2 => "milk", 3 => "rice" }
+        );
+        assert_eq!(
+            grouped["north"],
+            hashmap! {
+                1 => hashmap! { itemset![0] => 3, itemset![2] => 2 },
+                2 => hashmap! { itemset![0, 2] => 2 },
+            }
+        );
+        assert_eq!(
+            grouped["south"],
+            hashmap! {
+                1 => hashmap! { itemset![2] => 2, itemset![3] => 2 },
+                2 => hashmap! { itemset![2, 3] => 2 },
+            }
+        );
+        // "milk" is frequent in both groups, under the same id
+        assert_eq!(grouped["north"][&1][&itemset![2]], 2);
+        assert_eq!(grouped["south"][&1][&itemset![2]], 2);
+        assert_eq!(
+            names(&grouped["south"], &inventory, 1),
+            vec![vec!["milk"], vec!["rice"]]
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use std::{collections::HashMap, convert::TryFrom, hash::BuildHasher};
+
+use crate::{
+    itemsets::{count::levels_iter, support::SupportFraction},
+    types::{
+        FrequentItemsets, HashState, Inventory, ItemId, Itemset, ItemsetLength, RawTransaction,
+    },
+};
+
+/// Handle to an itemset interned in an [`ItemsetPool`].
+///
+/// Handles are plain indices, so they are cheap to copy, hash and compare.
+/// The pool stores each itemset once, so two handles from the same pool are
+/// equal exactly when their itemsets are, and maps can be keyed by handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ItemsetHandle(u32);
+
+/// Frequent itemsets keyed by handles into an [`ItemsetPool`], by level, the
+/// interned counterpart of [`FrequentItemsets`].
+pub type InternedItemsets =
+    HashMap<ItemsetLength, HashMap<ItemsetHandle, u32, HashState>, HashState>;
+
+/// Store of unique itemsets, all kept back to back in one buffer.
+///
+/// Where an [`Itemset`] key owns a heap allocation of its own, interning one
+/// copies its items onto the end of the shared buffer and hands out an
+/// [`ItemsetHandle`]. The buffer, the item offsets and the lookup table grow
+/// by doubling, so storing n itemsets takes O(log n) allocations instead of n.
+#[derive(Debug, Clone)]
+pub struct ItemsetPool {
+    items: Vec<ItemId>,
+    /// Itemset `i` is `items[starts[i]..starts[i + 1]]`.
+    starts: Vec<usize>,
+    /// First itemset with each content hash; the rest are chained in `next`.
+    buckets: HashMap<u64, ItemsetHandle, HashState>,
+    next: Vec<Option<ItemsetHandle>>,
+    hasher: HashState,
+}
+
+impl ItemsetPool {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            starts: vec![0],
+            buckets: HashMap::default(),
+            next: Vec::new(),
+            hasher: HashState::default(),
+        }
+    }
+
+    /// Handle of `itemset`, storing it if it is not in the pool yet.
+    /// Items are taken in the order given, so `[1, 2]` and `[2, 1]` differ.
+    ///
+    /// # Panics
+    ///
+    /// If the pool already holds `u32::MAX` itemsets and `itemset` is new.
+    pub fn intern(&mut self, itemset: &[ItemId]) -> ItemsetHandle {
+        let hash = self.hasher.hash_one(itemset);
+        if let Some(handle) = self.find_hashed(itemset, hash) {
+            return handle;
+        }
+
+        let handle = ItemsetHandle(
+            u32::try_from(self.len()).expect("an ItemsetPool holds at most u32::MAX itemsets"),
+        );
+        self.items.extend_from_slice(itemset);
+        self.starts.push(self.items.len());
+        self.next.push(self.buckets.insert(hash, handle));
+        handle
+    }
+
+    /// Handle of `itemset` if it has been interned.
+    pub fn find(&self, itemset: &[ItemId]) -> Option<ItemsetHandle> {
+        self.find_hashed(itemset, self.hasher.hash_one(itemset))
+    }
+
+    /// Items of the itemset behind `handle`.
+    ///
+    /// # Panics
+    ///
+    /// If `handle` comes from another pool holding fewer itemsets.
+    pub fn get(&self, handle: ItemsetHandle) -> &[ItemId] {
+        let i = handle.0 as usize;
+        &self.items[self.starts[i]..self.starts[i + 1]]
+    }
+
+    /// Number of itemsets interned.
+    pub fn len(&self) -> usize {
+        self.starts.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copy interned itemsets out into the usual [`FrequentItemsets`], one
+    /// [`Itemset`] per key.
+    pub fn resolve(&self, itemsets: &InternedItemsets) -> FrequentItemsets {
+        itemsets
+            .iter()
+            .map(|(&size, itemset_counts)| {
+                let itemset_counts = itemset_counts
+                    .iter()
+                    .map(|(&handle, &count)| (Itemset::from(self.get(handle)), count))
+                    .collect();
+                (size, itemset_counts)
+            })
+            .collect()
+    }
+
+    fn find_hashed(&self, itemset: &[ItemId], hash: u64) -> Option<ItemsetHandle> {
+        let mut candidate = self.buckets.get(&hash).copied();
+        while let Some(handle) = candidate {
+            if self.get(handle) == itemset {
+                return Some(handle);
+            }
+            candidate = self.next[handle.0 as usize];
+        }
+        None
+    }
+}
+
+impl Default for ItemsetPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Same itemsets as
+/// [`generate_frequent_itemsets`](crate::itemsets::count::generate_frequent_itemsets),
+/// stored in an [`ItemsetPool`] and keyed by handle.
+///
+/// Levels come from [`levels_iter`], and each one is interned and dropped as
+/// soon as it arrives, so the result ends up holding each frequent itemset
+/// exactly once in one buffer rather than one allocation apiece. On dense
+/// data, where levels run to many thousands of itemsets, this keeps the
+/// result to a handful of allocations; see the `allocations` example.
+pub fn generate_frequent_itemsets_interned(
+    raw_transactions: Vec<RawTransaction>,
+    min_support: SupportFraction,
+    k: ItemsetLength,
+) -> (InternedItemsets, ItemsetPool, Inventory) {
+    let mut pool = ItemsetPool::new();
+    let mut all_frequent_itemsets: InternedItemsets = HashMap::default();
+
+    let mut levels = levels_iter(raw_transactions, min_support, k);
+    for (size, itemset_counts) in &mut levels {
+        let itemset_counts = itemset_counts
+            .into_iter()
+            .map(|(itemset, count)| (pool.intern(&itemset), count))
+            .collect();
+        all_frequent_itemsets.insert(size, itemset_counts);
+    }
+
+    (all_frequent_itemsets, pool, levels.into_inventory())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::MiningResult;
+
+    #[test]
+    fn test_pool_interns_once() {
+        let mut pool = ItemsetPool::new();
+        let a = pool.intern(&[1, 2]);
+        let b = pool.intern(&[1, 3]);
+
+        assert_ne!(a, b);
+        assert_eq!(pool.intern(&[1, 2]), a);
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.get(b), &[1, 3]);
+        assert_eq!(pool.find(&[1, 3]), Some(b));
+        assert_eq!(pool.find(&[3, 1]), None);
+
+        // equal itemsets share a handle, so they land on one map key
+        let mut counts: HashMap<ItemsetHandle, u32, HashState> = HashMap::default();
+        *counts.entry(a).or_insert(0) += 1;
+        *counts.entry(pool.intern(&[1, 2])).or_insert(0) += 1;
+        assert_eq!(counts[&a], 2);
+    }
+
+    #[test]
+    fn test_empty_pool() {
+        let mut pool = ItemsetPool::new();
+        assert!(pool.is_empty());
+
+        let empty = pool.intern(&[]);
+        assert_eq!(pool.get(empty), &[] as &[ItemId]);
+        assert_eq!(pool.intern(&[]), empty);
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn test_interned_matches_plain_mine() {
+        let items = ["a", "b", "c", "d", "e", "f", "g"];
+        let raw_transactions: Vec<RawTransaction> = (0..80_usize)
+            .map(|tid| {
+                items
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| (tid * (i + 3) + i) % 5 < 3)
+                    .map(|(_, &item)| item)
+                    .collect()
+            })
+            .collect();
+        let N = raw_transactions.len();
+
+        let (interned, pool, inventory) =
+            generate_frequent_itemsets_interned(raw_transactions.clone(), SupportFraction(0.2), 4);
+        let direct = MiningResult::mine(raw_transactions, SupportFraction(0.2), 4);
+
+        assert_eq!(pool.len(), interned.values().map(|level| level.len()).sum::<usize>());
+        let interned = MiningResult::new(pool.resolve(&interned), inventory, N);
+        assert!(direct.levels[2].num_itemsets > 0);
+        assert_eq!(interned.levels, direct.levels);
+        assert_eq!(interned.named(), direct.named());
+    }
+
+    #[test]
+    fn test_interned_matches_plain_mine_for_short_k() {
+        use crate::itemsets::count::generate_frequent_itemsets;
+
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["bread", "milk"],
+            vec!["bread", "milk", "jam"],
+            vec!["bread", "jam"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        for k in 0..=2 {
+            let (interned, pool, _) = generate_frequent_itemsets_interned(
+                raw_transactions.clone(),
+                SupportFraction(0.5),
+                k,
+            );
+            let (direct, _) =
+                generate_frequent_itemsets(raw_transactions.clone(), SupportFraction(0.5), k);
+            assert_eq!(pool.resolve(&interned), direct, "k = {}", k);
+        }
+    }
+}
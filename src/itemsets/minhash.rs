@@ -0,0 +1,139 @@
+use crate::{
+    itemsets::universal::{draw_universal_hashes, universal_hash},
+    types::{HashState, ItemId, Transaction},
+};
+use std::collections::HashMap;
+
+/// Estimated Jaccard similarity between the transaction sets of every pair of
+/// items that agree on at least one of `num_hashes` MinHash values, keyed by
+/// `(smaller id, larger id)`.
+///
+/// Each item is summarised by the minimum, over the transactions it occurs
+/// in, of each of `num_hashes` random hash functions of the transaction
+/// index. Two items agree on a hash with probability equal to the Jaccard
+/// similarity of their transaction sets, so the fraction of agreeing hashes
+/// estimates it, with a standard error of at most `0.5 / sqrt(num_hashes)`.
+///
+/// Pairs are only found through shared hash values, so pairs that never
+/// co-occur, and most dissimilar ones, are never enumerated and are left out;
+/// their estimate is 0. Memory is `num_hashes` values per item. The same
+/// `seed` always draws the same hash functions.
+pub fn approx_item_similarities(
+    transactions: &[Transaction],
+    num_hashes: usize,
+    seed: u64,
+) -> HashMap<(ItemId, ItemId), f32, HashState> {
+    let hashes = draw_universal_hashes(num_hashes, seed);
+
+    let mut signatures: HashMap<ItemId, Vec<u64>, HashState> = HashMap::default();
+    for (tid, transaction) in transactions.iter().enumerate() {
+        let row: Vec<u64> = hashes
+            .iter()
+            .map(|&(a, b)| universal_hash(a, b, tid as u64))
+            .collect();
+        for &item in transaction {
+            let signature = signatures
+                .entry(item)
+                .or_insert_with(|| vec![u64::MAX; num_hashes]);
+            for (min_hash, &hash) in signature.iter_mut().zip(&row) {
+                *min_hash = (*min_hash).min(hash);
+            }
+        }
+    }
+
+    // items that agree on a hash fall into the same bucket for it
+    let mut agreements: HashMap<(ItemId, ItemId), u32, HashState> = HashMap::default();
+    for i in 0..num_hashes {
+        let mut buckets: HashMap<u64, Vec<ItemId>, HashState> = HashMap::default();
+        for (&item, signature) in &signatures {
+            buckets.entry(signature[i]).or_default().push(item);
+        }
+
+        for mut items in buckets.into_values().filter(|items| items.len() > 1) {
+            items.sort_unstable();
+            for (j, &a) in items.iter().enumerate() {
+                for &b in &items[(j + 1)..] {
+                    *agreements.entry((a, b)).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    agreements
+        .into_iter()
+        .map(|(pair, count)| (pair, count as f32 / num_hashes as f32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    fn jaccard(transactions: &[Transaction], a: ItemId, b: ItemId) -> f32 {
+        let has = |transaction: &Transaction, item| transaction.contains(&item);
+        let both = transactions
+            .iter()
+            .filter(|t| has(t, a) && has(t, b))
+            .count();
+        let either = transactions
+            .iter()
+            .filter(|t| has(t, a) || has(t, b))
+            .count();
+        both as f32 / either as f32
+    }
+
+    fn transactions() -> Vec<Transaction> {
+        // item 0 in every other transaction, 1 mostly alongside 0, 2 in every
+        // third, 3 in the first half and 4 always with 3
+        (0..60)
+            .map(|tid| {
+                let mut transaction = vec![];
+                if tid % 2 == 0 {
+                    transaction.push(0);
+                }
+                if tid % 2 == 0 && tid % 10 != 0 || tid % 15 == 1 {
+                    transaction.push(1);
+                }
+                if tid % 3 == 0 {
+                    transaction.push(2);
+                }
+                if tid < 30 {
+                    transaction.extend(vec![3, 4]);
+                }
+                transaction
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_approx_item_similarities_close_to_exact() {
+        let transactions = transactions();
+        let similarities = approx_item_similarities(&transactions, 500, 42);
+
+        for (a, b) in (0..5).tuple_combinations() {
+            let estimate = similarities.get(&(a, b)).copied().unwrap_or(0.0);
+            let exact = jaccard(&transactions, a, b);
+            assert!(
+                (estimate - exact).abs() < 0.1,
+                "({}, {}): estimated {}, exact {}",
+                a,
+                b,
+                estimate,
+                exact
+            );
+        }
+        assert_eq!(similarities[&(3, 4)], 1.0);
+    }
+
+    #[test]
+    fn test_approx_item_similarities_deterministic() {
+        let transactions = transactions();
+
+        assert_eq!(
+            approx_item_similarities(&transactions, 64, 7),
+            approx_item_similarities(&transactions, 64, 7)
+        );
+        assert!(approx_item_similarities(&transactions, 0, 7).is_empty());
+    }
+}
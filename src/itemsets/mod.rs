@@ -1,2 +1,17 @@
+pub mod compare;
+pub mod condensed;
 pub mod count;
+pub mod counter;
+pub mod encode;
+pub mod estimate;
+pub mod grouped;
+pub mod intern;
+pub mod minhash;
+pub mod options;
+pub mod query;
 mod search;
+pub mod sketch;
+pub mod source;
+pub mod support;
+pub mod tune;
+mod universal;
@@ -0,0 +1,193 @@
+use crate::{itemsets::support::SupportFraction, types::ItemsetLength};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Limits and switches for a mining run.
+#[derive(Debug, Clone, Default)]
+pub struct MiningOptions {
+    /// Maximum number of candidates any single level may generate.
+    /// The check uses an upper bound computed from the previous level's size,
+    /// before any candidate is materialised. `None` means no limit.
+    pub candidate_budget: Option<usize>,
+    /// How the support threshold `min_support * N` is rounded to a count.
+    pub rounding: RoundingMode,
+    /// Transactions with fewer items are dropped before counting, and do not
+    /// count towards N. `0` keeps every transaction.
+    pub min_transaction_len: usize,
+    /// Log a warning listing the transactions with more items than this, which
+    /// tend to dominate counting cost. See
+    /// [`oversized_transactions`](crate::preprocess::oversized_transactions).
+    pub warn_transaction_len: Option<usize>,
+    /// Cap transactions at this many items after the 1-itemset filter, keeping
+    /// each one's highest-support items. Trades completeness for bounded cost;
+    /// see [`truncate_transactions`](crate::preprocess::truncate_transactions).
+    pub max_transaction_len: Option<usize>,
+    /// Per-level overrides of `min_support`: entry `i` is the threshold for
+    /// itemsets of `i + 1` items, and levels past the end use `min_support`.
+    /// Each level is counted against its own threshold, but candidates are
+    /// still generated from the itemsets kept at the previous level, so a
+    /// threshold that drops from one level to the next cannot bring back
+    /// itemsets whose subsets were pruned; only raising it is guaranteed to be
+    /// exact. Empty by default.
+    pub level_min_support: Vec<SupportFraction>,
+    /// Whether levels with no frequent itemsets appear in the result.
+    pub empty_levels: EmptyLevels,
+    /// How candidates of each level from the third on are generated from the
+    /// previous level's itemsets.
+    pub candidate_strategy: CandidateStrategy,
+    /// Set this flag, e.g. from another thread, to stop the run with
+    /// [`AprioriError::Cancelled`](crate::error::AprioriError::Cancelled). It is
+    /// checked at the start of every level and between chunks of counting
+    /// work, so a run stops soon after, even in the middle of a level.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl MiningOptions {
+    /// Support count for itemsets of `size` items out of `N` transactions,
+    /// `default` unless [`level_min_support`](Self::level_min_support) overrides it.
+    pub(crate) fn level_support_count(
+        &self,
+        size: ItemsetLength,
+        N: usize,
+        default: usize,
+    ) -> usize {
+        self.level_min_support
+            .get(size - 1)
+            .map_or(default, |&min_support| {
+                min_support.support_count(N, self.rounding)
+            })
+    }
+
+    /// The cancel flag, if there is one.
+    pub(crate) fn cancel_flag(&self) -> Option<&AtomicBool> {
+        self.cancel.as_deref()
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        is_set(self.cancel_flag())
+    }
+}
+
+/// Whether `flag` is present and set.
+pub(crate) fn is_set(flag: Option<&AtomicBool>) -> bool {
+    flag.is_some_and(|flag| flag.load(Ordering::Relaxed))
+}
+
+/// What to do with levels that turn out to hold no frequent itemsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyLevels {
+    /// Keep them as empty maps, showing they were checked. Every level up to
+    /// `k` is then present; with `k = None`, mining stops at the first empty
+    /// level, which is not included. This is the default.
+    #[default]
+    Include,
+    /// Leave them out, so every level present has at least one itemset.
+    Omit,
+}
+
+/// How the candidates of a level are generated from the frequent itemsets of
+/// the previous one.
+///
+/// Both give the same frequent itemsets, since a candidate with an infrequent
+/// subset is itself infrequent; they differ in where the work goes. The one
+/// exception is a [`level_min_support`](MiningOptions::level_min_support)
+/// threshold that drops from one level to the next, under which `JoinOnly`
+/// may keep itemsets that `JoinAndPrune` rules out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CandidateStrategy {
+    /// Join itemsets sharing a prefix with `join_step` and count every
+    /// result. This is the default.
+    #[default]
+    JoinOnly,
+    /// Join, then drop the candidates with a subset missing from the previous
+    /// level before counting. Fewer candidates are counted, at the cost of a
+    /// lookup for each of their subsets.
+    JoinAndPrune,
+}
+
+/// How the fractional threshold `min_support * N` is turned into a support count.
+///
+/// Implementations differ at the boundary: with N = 4 and min_support = 0.3,
+/// `Ceil` requires 2 occurrences while `Floor` and `Round` accept 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Round up, so an itemset is frequent only if its support fraction is at
+    /// least `min_support`. This is the default.
+    #[default]
+    Ceil,
+    /// Round down, admitting itemsets whose support falls just short of `min_support`.
+    Floor,
+    /// Round to the nearest count, halves rounding up.
+    Round,
+}
+
+impl RoundingMode {
+    /// Minimum number of transactions an itemset must appear in to be frequent.
+    ///
+    /// The product is computed in `f64`, which stays exact to the count for N
+    /// well into the billions. Products within a millionth of a transaction
+    /// of a whole number (e.g. `0.07 * 100 = 7.000000000000001`, or a third
+    /// written to `f32` precision times 3) are treated as that number before
+    /// rounding. The margin is absolute, so it never grows to swallow a
+    /// fraction of a transaction that is really there.
+    pub fn support_count(self, min_support: f64, N: usize) -> usize {
+        let threshold = min_support * N as f64;
+        let nearest = threshold.round();
+        if (threshold - nearest).abs() <= 1e-6 {
+            return nearest as usize;
+        }
+        let count = match self {
+            RoundingMode::Ceil => threshold.ceil(),
+            RoundingMode::Floor => threshold.floor(),
+            RoundingMode::Round => threshold.round(),
+        };
+        count as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounding_modes_at_boundary() {
+        // 0.3 * 4 = 1.2
+        assert_eq!(RoundingMode::Ceil.support_count(0.3, 4), 2);
+        assert_eq!(RoundingMode::Floor.support_count(0.3, 4), 1);
+        assert_eq!(RoundingMode::Round.support_count(0.3, 4), 1);
+
+        // 0.4 * 4 = 1.6
+        assert_eq!(RoundingMode::Ceil.support_count(0.4, 4), 2);
+        assert_eq!(RoundingMode::Floor.support_count(0.4, 4), 1);
+        assert_eq!(RoundingMode::Round.support_count(0.4, 4), 2);
+    }
+
+    #[test]
+    fn test_rounding_modes_agree_on_whole_counts() {
+        for mode in [RoundingMode::Ceil, RoundingMode::Floor, RoundingMode::Round] {
+            assert_eq!(mode.support_count(0.5, 4), 2);
+        }
+    }
+
+    #[test]
+    fn test_support_count_large_n() {
+        // f32 cannot tell 50_000_001 from 50_000_000
+        assert_eq!(RoundingMode::Ceil.support_count(0.0001, 50_000_000), 5000);
+        assert_eq!(RoundingMode::Ceil.support_count(0.0001, 50_000_001), 5001);
+        assert_eq!(RoundingMode::Floor.support_count(0.0001, 50_000_001), 5000);
+        // and a SupportFraction, f32 as it is, reaches the same counts
+        assert_eq!(
+            SupportFraction(0.0001).support_count(50_000_001, RoundingMode::Ceil),
+            5001
+        );
+    }
+
+    #[test]
+    fn test_support_count_snaps_to_whole_counts() {
+        // 0.07 * 100 evaluates to 7.000000000000001
+        assert_eq!(RoundingMode::Ceil.support_count(0.07, 100), 7);
+        assert_eq!(RoundingMode::Floor.support_count(0.29, 100), 29);
+    }
+}
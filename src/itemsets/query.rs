@@ -0,0 +1,413 @@
+use rand::{rngs::StdRng, seq::index, SeedableRng};
+
+use crate::types::{
+    FrequentItemsets, HashState, Inventory, ItemId, ItemName, Itemset, ItemsetLength,
+};
+use std::collections::HashSet;
+
+/// Length of the longest itemsets found, i.e. the highest level that is not empty.
+/// Returns `None` if no itemset is frequent.
+pub fn max_frequent_k(itemsets: &FrequentItemsets) -> Option<ItemsetLength> {
+    itemsets
+        .iter()
+        .filter(|(_, itemset_counts)| !itemset_counts.is_empty())
+        .map(|(&size, _)| size)
+        .max()
+}
+
+/// Number of frequent itemsets over all levels.
+pub fn count_all_itemsets(itemsets: &FrequentItemsets) -> usize {
+    itemsets
+        .values()
+        .map(|itemset_counts| itemset_counts.len())
+        .sum()
+}
+
+/// Number of frequent itemsets at each level, as `(level, count)` in
+/// ascending level order. Empty levels present in `itemsets` are included.
+pub fn count_per_level(itemsets: &FrequentItemsets) -> Vec<(ItemsetLength, usize)> {
+    let mut counts: Vec<(ItemsetLength, usize)> = itemsets
+        .iter()
+        .map(|(&size, itemset_counts)| (size, itemset_counts.len()))
+        .collect();
+    counts.sort_unstable();
+    counts
+}
+
+/// Every itemset at the highest non-empty level, i.e. the longest frequent
+/// itemsets, sorted by id. Empty if no itemset is frequent.
+pub fn longest_itemsets(itemsets: &FrequentItemsets) -> Vec<(Itemset, u32)> {
+    let mut longest: Vec<(Itemset, u32)> = max_frequent_k(itemsets)
+        .map(|k| {
+            itemsets[&k]
+                .iter()
+                .map(|(itemset, &count)| (itemset.clone(), count))
+                .collect()
+        })
+        .unwrap_or_default();
+    longest.sort_unstable();
+    longest
+}
+
+/// Every frequent itemset with its count, level by level in ascending length.
+/// Order within a level follows the map's iteration order.
+pub fn iter_all(itemsets: &FrequentItemsets) -> impl Iterator<Item = (&Itemset, u32)> {
+    let mut sizes: Vec<ItemsetLength> = itemsets.keys().copied().collect();
+    sizes.sort_unstable();
+    sizes
+        .into_iter()
+        .flat_map(move |size| itemsets[&size].iter().map(|(itemset, &count)| (itemset, count)))
+}
+
+/// Every item that appears in any itemset of any level.
+///
+/// With level 1 present this is the set of frequent items. On results that
+/// have been filtered, e.g. down to the levels of interest or to the
+/// itemsets behind a set of rules, it tells which items still take part.
+pub fn frequent_items(itemsets: &FrequentItemsets) -> HashSet<ItemId, HashState> {
+    itemsets
+        .values()
+        .flat_map(|itemset_counts| itemset_counts.keys())
+        .flatten()
+        .copied()
+        .collect()
+}
+
+/// Same as [`frequent_items`], resolved to names through `inventory`.
+pub fn frequent_item_names<'l>(
+    itemsets: &FrequentItemsets,
+    inventory: &Inventory<'l>,
+) -> HashSet<ItemName<'l>, HashState> {
+    frequent_items(itemsets)
+        .iter()
+        .map(|item_id| inventory[item_id])
+        .collect()
+}
+
+/// Pairwise joint support of the frequent items, e.g. for plotting a heatmap.
+///
+/// Returns the frequent 1-items in ascending id order together with a
+/// symmetric matrix indexed the same way. The diagonal holds each item's own
+/// support. Pairs that were not frequent enough to be kept get zero.
+pub fn cooccurrence_matrix(itemsets: &FrequentItemsets) -> (Vec<ItemId>, Vec<Vec<u32>>) {
+    let mut items: Vec<ItemId> = itemsets
+        .get(&1)
+        .map(|counts| counts.keys().map(|itemset| itemset[0]).collect())
+        .unwrap_or_default();
+    items.sort_unstable();
+
+    let mut matrix = vec![vec![0; items.len()]; items.len()];
+    let index = |item: ItemId| items.binary_search(&item).ok();
+
+    if let Some(counts) = itemsets.get(&1) {
+        for (itemset, &count) in counts {
+            if let Some(i) = index(itemset[0]) {
+                matrix[i][i] = count;
+            }
+        }
+    }
+    if let Some(counts) = itemsets.get(&2) {
+        for (pair, &count) in counts {
+            if let (Some(i), Some(j)) = (index(pair[0]), index(pair[1])) {
+                matrix[i][j] = count;
+                matrix[j][i] = count;
+            }
+        }
+    }
+
+    (items, matrix)
+}
+
+/// Draw `n` itemsets uniformly at random across all levels, for eyeballing
+/// a large result.
+///
+/// The same `seed` always gives the same sample for the same itemsets,
+/// whatever the map's iteration order. Itemsets come back ordered by length,
+/// then id. If there are fewer than `n` itemsets, all of them are returned.
+pub fn sample_itemsets(itemsets: &FrequentItemsets, n: usize, seed: u64) -> Vec<(Itemset, u32)> {
+    let mut all: Vec<(&Itemset, u32)> = iter_all(itemsets).collect();
+    all.sort_unstable_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut chosen = index::sample(&mut rng, all.len(), n.min(all.len())).into_vec();
+    chosen.sort_unstable();
+
+    chosen
+        .into_iter()
+        .map(|i| (all[i].0.clone(), all[i].1))
+        .collect()
+}
+
+/// Frequent itemsets one item longer than `itemset` that contain it, with
+/// their counts, sorted by id, for drilling down from `itemset` to more
+/// specific combinations.
+///
+/// Only the next level is scanned. `itemset` need not be frequent itself.
+pub fn supersets(itemsets: &FrequentItemsets, itemset: &[ItemId]) -> Vec<(Itemset, u32)> {
+    adjacent(itemsets, itemset.len() + 1, |superset| {
+        is_subset(itemset, superset)
+    })
+}
+
+/// Frequent itemsets one item shorter than `itemset` that it contains, with
+/// their counts, sorted by id, for drilling up from `itemset` to more
+/// general ones.
+///
+/// Only the previous level is scanned, so the empty itemset, which is not
+/// kept, is never returned. `itemset` need not be frequent itself.
+pub fn subsets(itemsets: &FrequentItemsets, itemset: &[ItemId]) -> Vec<(Itemset, u32)> {
+    if itemset.len() < 2 {
+        return vec![];
+    }
+    adjacent(itemsets, itemset.len() - 1, |subset| {
+        is_subset(subset, itemset)
+    })
+}
+
+/// The itemsets of level `size` satisfying `related`, sorted by id.
+fn adjacent(
+    itemsets: &FrequentItemsets,
+    size: ItemsetLength,
+    related: impl Fn(&[ItemId]) -> bool,
+) -> Vec<(Itemset, u32)> {
+    let mut adjacent: Vec<(Itemset, u32)> = itemsets
+        .get(&size)
+        .map(|itemset_counts| {
+            itemset_counts
+                .iter()
+                .filter(|(itemset, _)| related(itemset))
+                .map(|(itemset, &count)| (itemset.clone(), count))
+                .collect()
+        })
+        .unwrap_or_default();
+    adjacent.sort_unstable();
+    adjacent
+}
+
+/// Whether every item of `subset` is in `superset`.
+fn is_subset(subset: &[ItemId], superset: &[ItemId]) -> bool {
+    subset.iter().all(|item| superset.contains(item))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_frequent_k_skips_empty_levels() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 3,
+                itemset![1] => 3,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 2,
+            },
+            3 => hashmap! {},
+        };
+        assert_eq!(max_frequent_k(&itemsets), Some(2));
+    }
+
+    #[test]
+    fn test_frequent_items() {
+        // 3 and 4 are frequent on their own but in no longer itemset
+        let mut itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 4,
+                itemset![1] => 4,
+                itemset![2] => 3,
+                itemset![3] => 2,
+                itemset![4] => 2,
+            },
+            2 => hashmap! { itemset![0, 1] => 3, itemset![1, 2] => 2 },
+            3 => hashmap! {},
+        };
+        let inventory: Inventory = hashmap! {
+            0 => "bread", 1 => "milk", 2 => "cheese", 3 => "jam", 4 => "tea",
+        };
+
+        let items = |ids: Vec<ItemId>| ids.into_iter().collect::<HashSet<_, HashState>>();
+        assert_eq!(frequent_items(&itemsets), items(vec![0, 1, 2, 3, 4]));
+
+        itemsets.remove(&1);
+        assert_eq!(frequent_items(&itemsets), items(vec![0, 1, 2]));
+        assert_eq!(
+            frequent_item_names(&itemsets, &inventory),
+            vec!["bread", "milk", "cheese"].into_iter().collect()
+        );
+
+        assert!(frequent_items(&FrequentItemsets::default()).is_empty());
+    }
+
+    #[test]
+    fn test_max_frequent_k_none() {
+        let itemsets: FrequentItemsets = hashmap! { 1 => hashmap! {} };
+        assert_eq!(max_frequent_k(&itemsets), None);
+        assert_eq!(max_frequent_k(&FrequentItemsets::default()), None);
+    }
+
+    #[test]
+    fn test_count_all_and_per_level() {
+        let itemsets: FrequentItemsets = hashmap! {
+            3 => hashmap! { itemset![0, 1, 2] => 2 },
+            1 => hashmap! { itemset![0] => 4, itemset![1] => 4, itemset![2] => 3 },
+            2 => hashmap! { itemset![0, 1] => 3, itemset![0, 2] => 3, itemset![1, 2] => 2 },
+            4 => hashmap! {},
+        };
+
+        assert_eq!(count_all_itemsets(&itemsets), 7);
+        assert_eq!(
+            count_per_level(&itemsets),
+            vec![(1, 3), (2, 3), (3, 1), (4, 0)]
+        );
+
+        assert_eq!(count_all_itemsets(&FrequentItemsets::default()), 0);
+        assert!(count_per_level(&FrequentItemsets::default()).is_empty());
+    }
+
+    #[test]
+    fn test_longest_itemsets() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 4,
+                itemset![1] => 4,
+                itemset![2] => 3,
+                itemset![3] => 3,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 3,
+                itemset![0, 2] => 3,
+                itemset![1, 2] => 2,
+                itemset![2, 3] => 2,
+            },
+            3 => hashmap! { itemset![0, 1, 2] => 2, itemset![1, 2, 3] => 2 },
+            4 => hashmap! {},
+        };
+
+        assert_eq!(
+            longest_itemsets(&itemsets),
+            vec![(itemset![0, 1, 2], 2), (itemset![1, 2, 3], 2)]
+        );
+        assert!(longest_itemsets(&FrequentItemsets::default()).is_empty());
+    }
+
+    #[test]
+    fn test_iter_all() {
+        let itemsets: FrequentItemsets = hashmap! {
+            2 => hashmap! {
+                itemset![0, 1] => 2,
+                itemset![1, 2] => 2,
+            },
+            1 => hashmap! {
+                itemset![0] => 3,
+                itemset![1] => 4,
+                itemset![2] => 2,
+            },
+            3 => hashmap! {},
+        };
+
+        let mut all: Vec<(&Itemset, u32)> = iter_all(&itemsets).collect();
+
+        let sizes: Vec<usize> = all.iter().map(|(itemset, _)| itemset.len()).collect();
+        assert_eq!(sizes, vec![1, 1, 1, 2, 2]);
+
+        let mut expected: Vec<(&Itemset, u32)> = itemsets
+            .values()
+            .flat_map(|counts| counts.iter().map(|(itemset, &count)| (itemset, count)))
+            .collect();
+        expected.sort();
+        all.sort();
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn test_cooccurrence_matrix() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![4] => 3,
+                itemset![1] => 5,
+                itemset![7] => 2,
+            },
+            2 => hashmap! {
+                itemset![1, 4] => 3,
+                itemset![1, 7] => 2,
+            },
+        };
+
+        let (items, matrix) = cooccurrence_matrix(&itemsets);
+
+        assert_eq!(items, vec![1, 4, 7]);
+        assert_eq!(
+            matrix,
+            vec![
+                vec![5, 3, 2],
+                vec![3, 3, 0],
+                vec![2, 0, 2],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cooccurrence_matrix_empty() {
+        let (items, matrix) = cooccurrence_matrix(&FrequentItemsets::default());
+        assert!(items.is_empty());
+        assert!(matrix.is_empty());
+    }
+
+    #[test]
+    fn test_sample_itemsets() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => (0..20).map(|item| (itemset![item], 10)).collect(),
+            2 => (0..19).map(|item| (itemset![item, item + 1], 5)).collect(),
+        };
+
+        let sample = sample_itemsets(&itemsets, 5, 42);
+        assert_eq!(sample.len(), 5);
+        assert_eq!(sample, sample_itemsets(&itemsets, 5, 42));
+        // rebuilt maps can iterate in a different order but sample the same
+        let rebuilt: FrequentItemsets = itemsets
+            .iter()
+            .map(|(&size, counts)| (size, counts.iter().map(|(i, &c)| (i.clone(), c)).collect()))
+            .collect();
+        assert_eq!(sample, sample_itemsets(&rebuilt, 5, 42));
+        assert_ne!(sample, sample_itemsets(&itemsets, 5, 7));
+
+        for (itemset, count) in &sample {
+            assert_eq!(itemsets[&itemset.len()][itemset], *count);
+        }
+
+        assert_eq!(sample_itemsets(&itemsets, 100, 42).len(), 39);
+        assert!(sample_itemsets(&FrequentItemsets::default(), 5, 42).is_empty());
+    }
+
+    #[test]
+    fn test_supersets_and_subsets() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 5,
+                itemset![1] => 4,
+                itemset![2] => 4,
+                itemset![3] => 3,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 3,
+                itemset![0, 2] => 3,
+                itemset![1, 2] => 3,
+                itemset![2, 3] => 2,
+            },
+            3 => hashmap! { itemset![0, 1, 2] => 2 },
+        };
+
+        assert_eq!(supersets(&itemsets, &[0, 1]), vec![(itemset![0, 1, 2], 2)]);
+        assert_eq!(
+            subsets(&itemsets, &[0, 1]),
+            vec![(itemset![0], 5), (itemset![1], 4)]
+        );
+
+        // {2, 3} has no frequent superset, and nothing lies past the top level
+        assert!(supersets(&itemsets, &[2, 3]).is_empty());
+        assert!(supersets(&itemsets, &[0, 1, 2]).is_empty());
+        assert_eq!(supersets(&itemsets, &[3]), vec![(itemset![2, 3], 2)]);
+        assert!(subsets(&itemsets, &[3]).is_empty());
+    }
+}
@@ -1,41 +1,124 @@
 use itertools::Itertools;
 
-use crate::types::{ItemId, Itemset, ItemsetCounts};
+use crate::{
+    itemsets::options::CandidateStrategy,
+    types::{ItemId, Itemset, ItemsetCounts},
+};
 
-/// target k
-pub fn generate_candidates_from_prev(prev_frequent_itemsets: &ItemsetCounts) -> Vec<Itemset> {
-    let curr: Vec<Itemset> = prev_frequent_itemsets.keys().cloned().collect();
-    join_step(curr)
+/// Candidates of size k + 1 from the frequent k-itemsets, generated as
+/// `strategy` says.
+pub fn generate_candidates_from_prev(
+    prev_frequent_itemsets: &ItemsetCounts,
+    strategy: CandidateStrategy,
+) -> Vec<Itemset> {
+    let curr: Vec<&Itemset> = prev_frequent_itemsets.keys().collect();
+    let mut candidates = join_step(curr);
+    if strategy == CandidateStrategy::JoinAndPrune {
+        candidates.retain(|candidate| has_frequent_subsets(candidate, prev_frequent_itemsets));
+    }
+    candidates
+}
+
+/// Whether every subset of `candidate` one item shorter is in
+/// `prev_frequent_itemsets`.
+///
+/// The two subsets leaving out one of the last two items are the itemsets the
+/// candidate was joined from, so only the others are looked up.
+fn has_frequent_subsets(candidate: &[ItemId], prev_frequent_itemsets: &ItemsetCounts) -> bool {
+    let mut subset: Vec<ItemId> = Vec::with_capacity(candidate.len());
+    (0..candidate.len().saturating_sub(2)).all(|skipped| {
+        subset.clear();
+        subset.extend_from_slice(&candidate[..skipped]);
+        subset.extend_from_slice(&candidate[skipped + 1..]);
+        prev_frequent_itemsets.contains_key(subset.as_slice())
+    })
+}
+
+/// Upper bound on the number of candidates the join step can produce from
+/// `prev_level_size` itemsets, i.e. every pair sharing a prefix.
+/// For the 2-itemsets, where every pair of frequent items is a candidate, the bound is exact.
+pub fn estimate_candidate_count(prev_level_size: usize) -> usize {
+    prev_level_size.saturating_mul(prev_level_size.saturating_sub(1)) / 2
 }
 
 /// Join k length itemsets into k + 1 length itemsets.
-/// 
+///
+/// Every itemset must hold the same number k >= 1 of items, in strictly
+/// ascending order. Two itemsets are joined when they agree on their first
+/// k - 1 items, giving that prefix followed by both of their last items, so
+/// candidates come out sorted as well. No subset pruning is done: a candidate
+/// may have k-subsets missing from `itemsets`.
+///
+/// Input breaking the contract (empty itemsets, differing lengths, unsorted or
+/// repeated items) gives no candidates rather than a panic or a malformed
+/// join. Duplicate itemsets are joined once, and the candidates are returned
+/// in ascending order without duplicates, which debug builds assert.
+///
+/// Itemsets may be passed by reference, so that joining a level does not copy
+/// it; only the candidates are allocated.
+///
 /// Algorithm translated from
 /// https://github.com/tommyod/Efficient-Apriori/blob/master/efficient_apriori/itemsets.py
-pub fn join_step(mut itemsets: Vec<Itemset>) -> Vec<Itemset> {
-    if itemsets.is_empty() {
-        return vec![];
+pub fn join_step<S: AsRef<[ItemId]> + Ord>(itemsets: Vec<S>) -> Vec<Itemset> {
+    let mut final_itemsets: Vec<Itemset> = Vec::with_capacity(1024); // arbitrary
+    join_each(itemsets, |candidate| {
+        final_itemsets.push(Itemset::from(candidate))
+    });
+
+    // prefixes are visited in ascending order and each is followed by its
+    // pairs of tails in ascending order, so candidates come out ascending too,
+    // which also makes them unique
+    debug_assert!(
+        final_itemsets
+            .iter()
+            .all(|itemset| is_strictly_ascending(itemset)),
+        "join_step generated a candidate with unsorted or repeated items"
+    );
+    debug_assert!(
+        is_strictly_ascending(&final_itemsets),
+        "join_step generated unsorted or duplicate candidates"
+    );
+    final_itemsets
+}
+
+/// The join of [`join_step`], passing each candidate to `emit` in the same
+/// order instead of collecting them, so callers can store candidates however
+/// they like. The slice is only valid for the duration of the call.
+pub(crate) fn join_each<S: AsRef<[ItemId]> + Ord>(
+    mut itemsets: Vec<S>,
+    mut emit: impl FnMut(&[ItemId]),
+) {
+    let k = match itemsets.first().map(AsRef::as_ref) {
+        Some(itemset) if !itemset.is_empty() => itemset.len(),
+        _ => return,
+    };
+    let well_formed = |itemset: &S| {
+        let itemset = itemset.as_ref();
+        itemset.len() == k && is_strictly_ascending(itemset)
+    };
+    if !itemsets.iter().all(well_formed) {
+        return;
     }
 
     itemsets.sort_unstable();
+    itemsets.dedup();
 
-    let mut final_itemsets: Vec<Itemset> = Vec::with_capacity(1024); // arbitrary
-    let mut itemset_first_tuple: Itemset = Vec::with_capacity(itemsets[0].len() + 1);
+    let mut candidate: Vec<ItemId> = Vec::with_capacity(k + 1);
     let mut tail_items: Vec<ItemId> = Vec::with_capacity(itemsets.len()); // based on analysis of the first for loop
 
     let mut i = 0;
     while i < itemsets.len() {
         let mut skip = 1;
 
-        let (itemset_first, itemset_last) = itemsets[i].split_at(itemsets[i].len() - 1);
-        let itemset_last = itemset_last.to_owned().pop().unwrap();
+        let (itemset_first, itemset_last) = itemsets[i].as_ref().split_at(k - 1);
+        let itemset_last = itemset_last[0];
 
         tail_items.clear();
         tail_items.push(itemset_last);
 
-        for j in (i + 1)..itemsets.len() {
-            let (itemset_n_first, itemset_n_last) = itemsets[j].split_at(itemsets[j].len() - 1);
-            let itemset_n_last = itemset_n_last.to_owned().pop().unwrap();
+        for itemset_n in &itemsets[(i + 1)..] {
+            let (itemset_n_first, itemset_n_last) = itemset_n.as_ref().split_at(k - 1);
+            let itemset_n_last = itemset_n_last[0];
 
             if itemset_first == itemset_n_first {
                 tail_items.push(itemset_n_last);
@@ -45,61 +128,166 @@ pub fn join_step(mut itemsets: Vec<Itemset>) -> Vec<Itemset> {
             }
         }
 
-        for combi in tail_items.iter().combinations(2).sorted() {
-            itemset_first_tuple.clear();
-            itemset_first_tuple.extend(itemset_first);
-            let (a, b) = combi.split_at(1);
-            let a = *a.to_owned().pop().unwrap();
-            let b = *b.to_owned().pop().unwrap();
-
-            itemset_first_tuple.push(a);
-            itemset_first_tuple.push(b);
-            // itemset_first_tuple.sort_unstable();
-            final_itemsets.push(itemset_first_tuple.to_owned());
+        // the tails are ascending, so their pairs come out in sorted order
+        for (&a, &b) in tail_items.iter().tuple_combinations() {
+            candidate.clear();
+            candidate.extend_from_slice(itemset_first);
+            candidate.push(a);
+            candidate.push(b);
+            emit(&candidate);
         }
 
         i += skip;
     }
+}
 
-    final_itemsets
+fn is_strictly_ascending<T: Ord>(items: &[T]) -> bool {
+    items.windows(2).all(|pair| pair[0] < pair[1])
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_generate_candidates_join_and_prune() {
+        let prev: ItemsetCounts = hashmap! {
+            itemset![1, 2] => 3,
+            itemset![1, 3] => 3,
+            itemset![1, 4] => 3,
+            itemset![2, 3] => 3,
+            itemset![2, 4] => 3,
+        };
+
+        assert_eq!(
+            generate_candidates_from_prev(&prev, CandidateStrategy::JoinOnly),
+            vec![itemset![1, 2, 3], itemset![1, 2, 4], itemset![1, 3, 4], itemset![2, 3, 4]]
+        );
+        // {3, 4} is not frequent
+        assert_eq!(
+            generate_candidates_from_prev(&prev, CandidateStrategy::JoinAndPrune),
+            vec![itemset![1, 2, 3], itemset![1, 2, 4]]
+        );
+    }
+
     #[test]
     fn test_join_step() {
         let itemsets: Vec<Itemset> = vec![
-            vec![1, 2, 3],
-            vec![1, 2, 4],
-            vec![1, 3, 4],
-            vec![1, 3, 5],
-            vec![2, 3, 4],
+            itemset![1, 2, 3],
+            itemset![1, 2, 4],
+            itemset![1, 3, 4],
+            itemset![1, 3, 5],
+            itemset![2, 3, 4],
         ];
         let y = join_step(itemsets);
         assert_eq!(y.len(), 2);
-        assert!(y.contains(&vec![1, 2, 3, 4]));
-        assert!(y.contains(&vec![1, 3, 4, 5]));
+        assert!(y.contains(&itemset![1, 2, 3, 4]));
+        assert!(y.contains(&itemset![1, 3, 4, 5]));
     }
 
     #[test]
     fn test_join_step_2() {
         let itemsets: Vec<Itemset> =
-            vec![vec![1, 2, 3], vec![1, 2, 4], vec![1, 3, 4], vec![2, 3, 4]];
+            vec![itemset![1, 2, 3], itemset![1, 2, 4], itemset![1, 3, 4], itemset![2, 3, 4]];
         let y = join_step(itemsets);
         assert!(!y.is_empty());
-        assert!(y.contains(&vec![1, 2, 3, 4]));
+        assert!(y.contains(&itemset![1, 2, 3, 4]));
     }
 
     #[test]
     fn test_join_step_3() {
         let itemsets: Vec<Itemset> =
-            vec![vec![1, 2], vec![2, 3], vec![1, 3], vec![1, 4], vec![3, 4]];
+            vec![itemset![1, 2], itemset![2, 3], itemset![1, 3], itemset![1, 4], itemset![3, 4]];
         let y = join_step(itemsets);
         println!("{:?}", y);
         assert!(y.len() >= 2);
-        assert!(y.contains(&vec![1, 2, 3]));
-        assert!(y.contains(&vec![1, 3, 4]));
+        assert!(y.contains(&itemset![1, 2, 3]));
+        assert!(y.contains(&itemset![1, 3, 4]));
+    }
+
+    #[test]
+    fn test_join_step_single_items() {
+        let y = join_step(vec![vec![3], vec![1], vec![2]]);
+        assert_eq!(y, vec![itemset![1, 2], itemset![1, 3], itemset![2, 3]]);
+    }
+
+    #[test]
+    fn test_join_step_single_itemset() {
+        assert!(join_step(vec![vec![1, 2]]).is_empty());
+        assert!(join_step(Vec::<Itemset>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_join_step_no_shared_prefix() {
+        assert!(join_step(vec![vec![1, 2], vec![3, 4], vec![2, 5]]).is_empty());
+    }
+
+    #[test]
+    fn test_join_step_duplicates() {
+        let y = join_step(vec![vec![1, 2], vec![1, 3], vec![1, 2]]);
+        assert_eq!(y, vec![itemset![1, 2, 3]]);
+    }
+
+    #[test]
+    fn test_join_step_malformed_input() {
+        // empty itemsets
+        assert!(join_step(vec![vec![], vec![]]).is_empty());
+        assert!(join_step(vec![vec![1, 2], vec![]]).is_empty());
+        // differing lengths
+        assert!(join_step(vec![vec![1, 2], vec![1, 3], vec![1, 2, 4]]).is_empty());
+        // unsorted or repeated items
+        assert!(join_step(vec![vec![1, 3], vec![2, 1]]).is_empty());
+        assert!(join_step(vec![vec![1, 1], vec![1, 2]]).is_empty());
+    }
+
+    #[test]
+    fn test_join_step_colliding_joins() {
+        // every 3-subset of {1, .., 5} can be joined from three different
+        // pairs of 2-subsets, but only those sharing a prefix are joined
+        let itemsets: Vec<Itemset> = (1..=5)
+            .combinations(2)
+            .map(|itemset| Itemset::from(itemset.as_slice()))
+            .collect();
+        let y = join_step(itemsets);
+
+        let expected: Vec<Itemset> = (1..=5)
+            .combinations(3)
+            .map(|itemset| Itemset::from(itemset.as_slice()))
+            .collect();
+        assert_eq!(y, expected);
+    }
+
+    #[test]
+    fn test_join_step_candidates_unique_and_sorted() {
+        use rand::{rngs::StdRng, seq::index::sample, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for k in 1..=4 {
+            let itemsets: Vec<Itemset> = (0..60)
+                .map(|_| sample(&mut rng, 8, k).into_iter().sorted().collect())
+                .collect();
+            let y = join_step(itemsets.clone());
+
+            assert!(y.iter().all(|itemset| itemset.len() == k + 1));
+            assert!(y.iter().all(|itemset| is_strictly_ascending(itemset)));
+            assert!(is_strictly_ascending(&y), "duplicates in {:?}", y);
+            // every candidate comes from two input itemsets sharing a prefix
+            for candidate in &y {
+                let mut without_last = candidate.clone();
+                without_last.remove(k);
+                let mut without_second_last = candidate.clone();
+                without_second_last.remove(k - 1);
+                assert!(itemsets.contains(&without_last));
+                assert!(itemsets.contains(&without_second_last));
+            }
+        }
+    }
+
+    #[test]
+    fn test_estimate_candidate_count() {
+        assert_eq!(estimate_candidate_count(0), 0);
+        assert_eq!(estimate_candidate_count(1), 0);
+        assert_eq!(estimate_candidate_count(4), 6);
+        assert_eq!(estimate_candidate_count(usize::MAX), usize::MAX / 2);
     }
 }
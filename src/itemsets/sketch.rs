@@ -0,0 +1,180 @@
+use crate::{
+    itemsets::universal::{draw_universal_hashes, universal_hash},
+    types::{ItemName, RawTransaction},
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// Seed of the row hash functions, fixed so that sketches of the same data
+/// always agree.
+const SEED: u64 = 0x5eed;
+
+/// Approximate item counts in a Count-Min Sketch: `depth` rows of `width`
+/// counters, each row indexed by its own hash of the item name.
+///
+/// Memory is `width * depth` counters whatever the number of distinct items.
+/// See [`approx_item_counts`].
+#[derive(Debug, Clone)]
+pub struct SketchCounts {
+    width: usize,
+    hashes: Vec<(u64, u64)>,
+    counters: Vec<u32>,
+    num_occurrences: u64,
+}
+
+/// Count every item of `raw_transactions` into a Count-Min Sketch of `depth`
+/// rows of `width` counters.
+///
+/// A sketched count is never below the true count. It exceeds it by at most
+/// `e / width` times the total number of item occurrences with probability at
+/// least `1 - exp(-depth)`; see [`SketchCounts::error_bound`]. So for an error
+/// of at most `ε` times the occurrences with probability `1 - δ`, take
+/// `width = ceil(e / ε)` and `depth = ceil(ln(1 / δ))`.
+///
+/// # Panics
+///
+/// If `width` or `depth` is 0.
+pub fn approx_item_counts(
+    raw_transactions: &[RawTransaction],
+    width: usize,
+    depth: usize,
+) -> SketchCounts {
+    assert!(width > 0 && depth > 0, "the sketch must not be empty");
+
+    let mut sketch = SketchCounts {
+        width,
+        hashes: draw_universal_hashes(depth, SEED),
+        counters: vec![0; width * depth],
+        num_occurrences: 0,
+    };
+
+    for raw_transaction in raw_transactions {
+        for &item in raw_transaction {
+            let key = item_key(item);
+            for row in 0..depth {
+                let cell = sketch.cell(row, key);
+                sketch.counters[cell] += 1;
+            }
+            sketch.num_occurrences += 1;
+        }
+    }
+    sketch
+}
+
+impl SketchCounts {
+    /// Sketched count of `item`, at least the number of transactions holding it.
+    pub fn count(&self, item: ItemName) -> u32 {
+        let key = item_key(item);
+        (0..self.hashes.len())
+            .map(|row| self.counters[self.cell(row, key)])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Largest amount by which [`count`](Self::count) overestimates, except
+    /// with probability at most `exp(-depth)`: `e / width` times the total
+    /// number of item occurrences counted.
+    pub fn error_bound(&self) -> f64 {
+        std::f64::consts::E / self.width as f64 * self.num_occurrences as f64
+    }
+
+    /// Drop the items whose sketched count is below `min_support_count`.
+    ///
+    /// Since sketched counts never fall short, every item that is truly
+    /// frequent is kept, and mining the result exactly finds the same itemsets
+    /// as mining the input, with far fewer distinct items to count when most
+    /// of the vocabulary is rare. Transactions are kept even if emptied, so N
+    /// does not change.
+    pub fn retain_frequent<'l>(
+        &self,
+        mut raw_transactions: Vec<RawTransaction<'l>>,
+        min_support_count: usize,
+    ) -> Vec<RawTransaction<'l>> {
+        for raw_transaction in &mut raw_transactions {
+            raw_transaction.retain(|&item| self.count(item) as usize >= min_support_count);
+        }
+        raw_transactions
+    }
+
+    /// Index into `counters` of the cell of the item hashed to `key` in `row`.
+    fn cell(&self, row: usize, key: u64) -> usize {
+        let (a, b) = self.hashes[row];
+        row * self.width + (universal_hash(a, b, key) % self.width as u64) as usize
+    }
+}
+
+/// Fixed-key hash of an item name, fed to every row's hash function.
+fn item_key(item: ItemName) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::support::SupportFraction;
+    use crate::result::MiningResult;
+    use std::collections::HashMap;
+
+    /// 500 transactions, each with a few of 5 common items and one item seen
+    /// nowhere else.
+    fn transactions(rare: &[String]) -> Vec<RawTransaction<'_>> {
+        let common = ["a", "b", "c", "d", "e"];
+        rare.iter()
+            .enumerate()
+            .map(|(tid, rare_item)| {
+                let mut raw_transaction: RawTransaction = common
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| tid % (i + 2) == 0)
+                    .map(|(_, &item)| item)
+                    .collect();
+                raw_transaction.insert(rare_item.as_str());
+                raw_transaction
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_approx_item_counts_never_undercount() {
+        let rare: Vec<String> = (0..500).map(|i| format!("rare-{}", i)).collect();
+        let raw_transactions = transactions(&rare);
+        let sketch = approx_item_counts(&raw_transactions, 64, 4);
+
+        let mut exact: HashMap<ItemName, u32> = HashMap::new();
+        for &item in raw_transactions.iter().flatten() {
+            *exact.entry(item).or_insert(0) += 1;
+        }
+        for (&item, &count) in &exact {
+            let estimate = sketch.count(item);
+            assert!(estimate >= count, "{}: {} < {}", item, estimate, count);
+        }
+        assert!((sketch.count("a") - exact["a"]) as f64 <= sketch.error_bound());
+    }
+
+    #[test]
+    fn test_retain_frequent_keeps_frequent_items() {
+        let rare: Vec<String> = (0..500).map(|i| format!("rare-{}", i)).collect();
+        let raw_transactions = transactions(&rare);
+        let sketch = approx_item_counts(&raw_transactions, 1024, 4);
+
+        // "e" is in every 6th transaction, 84 of 500
+        let retained = sketch.retain_frequent(raw_transactions.clone(), 80);
+
+        assert_eq!(retained.len(), raw_transactions.len());
+        let num_rare_kept = retained
+            .iter()
+            .flatten()
+            .filter(|item| item.starts_with("rare"))
+            .count();
+        assert!(num_rare_kept < 50, "{} rare items kept", num_rare_kept);
+
+        assert_eq!(
+            MiningResult::mine(retained, SupportFraction(0.16), 2).named(),
+            MiningResult::mine(raw_transactions, SupportFraction(0.16), 2).named()
+        );
+    }
+}
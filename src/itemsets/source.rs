@@ -0,0 +1,484 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use log::warn;
+
+use crate::{
+    error::AprioriError,
+    itemsets::{
+        count::{check_support_count, collect_levels, is_frequent, LevelIter},
+        encode::Encoder,
+        options::{MiningOptions, RoundingMode},
+        support::SupportFraction,
+    },
+    types::{
+        FrequentItemsets, Inventory, ItemCounts, ItemId, ItemsetLength, RawTransaction,
+        Transaction,
+    },
+};
+
+/// Somewhere encoded transactions can be re-read from, once per level.
+pub trait TransactionSource {
+    /// Call `f` with every transaction, in order.
+    fn for_each(&self, f: &mut dyn FnMut(&[ItemId])) -> io::Result<()>;
+}
+
+impl TransactionSource for [Transaction] {
+    fn for_each(&self, f: &mut dyn FnMut(&[ItemId])) -> io::Result<()> {
+        self.iter().for_each(|transaction| f(transaction));
+        Ok(())
+    }
+}
+
+impl<S: TransactionSource + ?Sized> TransactionSource for &S {
+    fn for_each(&self, f: &mut dyn FnMut(&[ItemId])) -> io::Result<()> {
+        (**self).for_each(f)
+    }
+}
+
+impl TransactionSource for Vec<Transaction> {
+    fn for_each(&self, f: &mut dyn FnMut(&[ItemId])) -> io::Result<()> {
+        self.as_slice().for_each(f)
+    }
+}
+
+/// Encoded transactions kept in a file instead of memory.
+///
+/// Each transaction is stored as its length (`u32`) followed by its item ids (`u64`),
+/// all little-endian. Only one transaction is held in memory while scanning.
+#[derive(Debug, Clone)]
+pub struct FileTransactions {
+    path: PathBuf,
+}
+
+impl FileTransactions {
+    /// Write `transactions` to `path`, replacing any existing file.
+    pub fn create<P, I>(path: P, transactions: I) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = Transaction>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let mut writer = BufWriter::new(File::create(&path)?);
+
+        for transaction in transactions {
+            writer.write_all(&(transaction.len() as u32).to_le_bytes())?;
+            for item in transaction {
+                writer.write_all(&(item as u64).to_le_bytes())?;
+            }
+        }
+        writer.flush()?;
+
+        Ok(Self { path })
+    }
+
+    /// Use a file previously written by [`FileTransactions::create`].
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl TransactionSource for FileTransactions {
+    fn for_each(&self, f: &mut dyn FnMut(&[ItemId])) -> io::Result<()> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let mut len_buf = [0_u8; 4];
+        let mut item_buf = [0_u8; 8];
+        let mut transaction: Transaction = Vec::new();
+
+        loop {
+            match reader.read_exact(&mut len_buf) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            }
+
+            transaction.clear();
+            for _ in 0..u32::from_le_bytes(len_buf) {
+                reader.read_exact(&mut item_buf)?;
+                transaction.push(u64::from_le_bytes(item_buf) as ItemId);
+            }
+            f(&transaction);
+        }
+
+        Ok(())
+    }
+}
+
+/// Encode `raw_transactions` straight into a [`FileTransactions`] at `path`.
+///
+/// Transactions are pulled from the iterator one at a time and written out as
+/// soon as they are encoded, so neither the raw nor the encoded dataset is ever
+/// held in memory as a whole. Returns the unpruned item counts and the
+/// inventory alongside the file, ready for
+/// [`generate_frequent_itemsets_from_source`].
+pub fn encode_to_file<'l, I, P>(
+    raw_transactions: I,
+    path: P,
+) -> io::Result<(ItemCounts, Inventory<'l>, FileTransactions)>
+where
+    I: IntoIterator<Item = RawTransaction<'l>>,
+    P: AsRef<Path>,
+{
+    let mut encoder = Encoder::new();
+    let file = FileTransactions::create(
+        path,
+        raw_transactions
+            .into_iter()
+            .map(|raw_transaction| encoder.encode(&raw_transaction)),
+    )?;
+    let (item_counts, inventory) = encoder.finish();
+
+    Ok((item_counts, inventory, file))
+}
+
+/// Generate frequent itemsets by streaming `source` once per level.
+///
+/// Unlike [`generate_frequent_itemsets_id`](crate::itemsets::count::generate_frequent_itemsets_id),
+/// transactions never have to fit in memory at once, at the cost of re-reading
+/// the source and counting serially. The levels are the same.
+pub fn generate_frequent_itemsets_from_source<S: TransactionSource + ?Sized>(
+    source: &S,
+    min_support: SupportFraction,
+    k: ItemsetLength,
+) -> io::Result<FrequentItemsets> {
+    let options = MiningOptions::default();
+    let (item_counts, N) = count_source_items(source, &options)?;
+    let min_support_count = min_support.support_count(N, RoundingMode::default());
+
+    mine_source(source, item_counts, N, min_support_count, Some(k), &options).map_err(|err| {
+        match err {
+            AprioriError::Io { kind, message } => io::Error::new(kind, message),
+            err => unreachable!("default options impose no limits: {}", err),
+        }
+    })
+}
+
+/// Same as [`generate_frequent_itemsets_from_source`], honouring the limits
+/// in `options` and the threshold checks of
+/// [`try_generate_frequent_itemsets`](crate::itemsets::count::try_generate_frequent_itemsets).
+///
+/// Transactions shorter than [`MiningOptions::min_transaction_len`] are
+/// skipped every time `source` is read, so they count towards neither any
+/// itemset nor N. A failure to read `source` comes back as
+/// [`AprioriError::Io`].
+pub fn try_generate_frequent_itemsets_from_source<S: TransactionSource + ?Sized>(
+    source: &S,
+    min_support: SupportFraction,
+    k: Option<ItemsetLength>,
+    options: &MiningOptions,
+) -> Result<FrequentItemsets, AprioriError> {
+    let (item_counts, N) = count_source_items(source, options)?;
+    let min_support_count = check_support_count(
+        min_support.support_count(N, options.rounding),
+        N,
+        k,
+        options,
+    )?;
+
+    mine_source(source, item_counts, N, min_support_count, k, options)
+}
+
+/// Count every item in one pass over `source`, and the transactions long
+/// enough to be kept.
+fn count_source_items<S: TransactionSource + ?Sized>(
+    source: &S,
+    options: &MiningOptions,
+) -> io::Result<(ItemCounts, usize)> {
+    let mut item_counts: ItemCounts = HashMap::default();
+    let mut N = 0_usize;
+    let mut oversized = vec![];
+
+    source.for_each(&mut |transaction| {
+        if transaction.len() < options.min_transaction_len {
+            return;
+        }
+        if options
+            .warn_transaction_len
+            .is_some_and(|max_len| transaction.len() > max_len)
+        {
+            oversized.push(N);
+        }
+        N += 1;
+        for &item in transaction {
+            *item_counts.entry(item).or_insert(0) += 1;
+        }
+    })?;
+
+    if let Some(max_len) = options.warn_transaction_len {
+        if !oversized.is_empty() {
+            warn!(
+                "{} transactions have more than {} items: {:?}",
+                oversized.len(),
+                max_len,
+                oversized
+            );
+        }
+    }
+
+    Ok((item_counts, N))
+}
+
+/// Mine the levels of `source` from the counts of its items, the shared
+/// [`LevelIter`] re-reading it for each level above the first.
+fn mine_source<S: TransactionSource + ?Sized>(
+    source: &S,
+    mut item_counts: ItemCounts,
+    N: usize,
+    min_support_count: usize,
+    k: Option<ItemsetLength>,
+    options: &MiningOptions,
+) -> Result<FrequentItemsets, AprioriError> {
+    // 1-itemset
+    let level_1_support_count = options.level_support_count(1, N, min_support_count);
+    item_counts.retain(|_, &mut count| is_frequent(count as usize, level_1_support_count));
+
+    collect_levels(
+        LevelIter::from_source(source, item_counts, N, min_support_count, k, options),
+        options,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::count::generate_frequent_itemsets_id;
+    use crate::types::RawTransactionId;
+
+    fn transactions() -> Vec<Transaction> {
+        vec![
+            vec![0, 1],
+            vec![0, 2],
+            vec![0, 1, 2],
+            vec![1, 3],
+            vec![0, 1, 2, 3],
+        ]
+    }
+
+    fn expected(min_support: SupportFraction, k: ItemsetLength) -> FrequentItemsets {
+        let raw_transactions: Vec<RawTransactionId> = transactions()
+            .into_iter()
+            .map(|transaction| transaction.into_iter().collect())
+            .collect();
+        generate_frequent_itemsets_id(raw_transactions, min_support, k)
+    }
+
+    /// `N` transactions, the first `count` of them holding item 0, generated
+    /// on every read rather than stored.
+    struct Repeated {
+        N: usize,
+        count: usize,
+    }
+
+    impl TransactionSource for Repeated {
+        fn for_each(&self, f: &mut dyn FnMut(&[ItemId])) -> io::Result<()> {
+            for i in 0..self.N {
+                f(if i < self.count { &[0] } else { &[] });
+            }
+            Ok(())
+        }
+    }
+
+    /// Whether item 0 is frequent in `count` of `N` transactions.
+    fn is_frequent_in(
+        count: usize,
+        N: usize,
+        min_support: SupportFraction,
+        rounding: RoundingMode,
+    ) -> bool {
+        let options = MiningOptions {
+            rounding,
+            ..MiningOptions::default()
+        };
+        let frequent_itemsets = try_generate_frequent_itemsets_from_source(
+            &Repeated { N, count },
+            min_support,
+            Some(1),
+            &options,
+        )
+        .unwrap();
+        frequent_itemsets
+            .get(&1)
+            .is_some_and(|itemset_counts| !itemset_counts.is_empty())
+    }
+
+    #[test]
+    fn test_large_n_thresholds() {
+        let cases = [
+            // 0.2 * 25_000_002 = 5_000_000.4
+            (5_000_000, 25_000_002, 0.2, RoundingMode::Ceil, false),
+            (5_000_001, 25_000_002, 0.2, RoundingMode::Ceil, true),
+            // 0.5 * 10_000_001 = 5_000_000.5
+            (5_000_000, 10_000_001, 0.5, RoundingMode::Floor, true),
+            (5_000_000, 10_000_001, 0.5, RoundingMode::Ceil, false),
+            // 0.0001 * 50_000_001 = 5000.0001, which the f32 nearest 0.0001
+            // puts just under 5000
+            (5000, 50_000_001, 0.0001, RoundingMode::Ceil, false),
+            (5001, 50_000_001, 0.0001, RoundingMode::Ceil, true),
+        ];
+        for (count, N, min_support, rounding, frequent) in cases {
+            assert_eq!(
+                is_frequent_in(count, N, SupportFraction(min_support), rounding),
+                frequent,
+                "{} of {} at {} rounding {:?}",
+                count,
+                N,
+                min_support,
+                rounding
+            );
+        }
+    }
+
+    #[test]
+    fn test_in_memory_source() {
+        let frequent_itemsets =
+            generate_frequent_itemsets_from_source(&transactions(), SupportFraction(0.4), 3)
+                .unwrap();
+        assert_eq!(frequent_itemsets, expected(SupportFraction(0.4), 3));
+
+        // k = 0 is left to the shared level loop, as in memory
+        let frequent_itemsets =
+            generate_frequent_itemsets_from_source(&transactions(), SupportFraction(0.4), 0)
+                .unwrap();
+        assert_eq!(frequent_itemsets.len(), 2);
+        assert_eq!(frequent_itemsets, expected(SupportFraction(0.4), 0));
+    }
+
+    #[test]
+    fn test_zero_support_source() {
+        let frequent_itemsets =
+            generate_frequent_itemsets_from_source(&transactions(), SupportFraction(0.0), 3)
+                .unwrap();
+
+        assert!(frequent_itemsets
+            .values()
+            .flat_map(|itemset_counts| itemset_counts.values())
+            .all(|&count| count > 0));
+        assert_eq!(frequent_itemsets, expected(SupportFraction(0.0), 3));
+    }
+
+    #[test]
+    fn test_source_honours_options() {
+        use crate::itemsets::count::try_generate_frequent_itemsets_id;
+        use std::sync::{atomic::AtomicBool, Arc};
+
+        let raw_transactions: Vec<RawTransactionId> = transactions()
+            .into_iter()
+            .map(|transaction| transaction.into_iter().collect())
+            .collect();
+        let options = MiningOptions {
+            min_transaction_len: 3,
+            max_transaction_len: Some(2),
+            ..MiningOptions::default()
+        };
+
+        // k = None stops after the first empty level, as in memory
+        let frequent_itemsets = try_generate_frequent_itemsets_from_source(
+            &transactions(),
+            SupportFraction(1.0),
+            None,
+            &options,
+        )
+        .unwrap();
+        let (expected_itemsets, _) = try_generate_frequent_itemsets_id(
+            raw_transactions,
+            SupportFraction(1.0),
+            None,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(frequent_itemsets.len(), 2);
+        assert_eq!(frequent_itemsets, expected_itemsets);
+
+        let cancelled = MiningOptions {
+            cancel: Some(Arc::new(AtomicBool::new(true))),
+            ..MiningOptions::default()
+        };
+        assert_eq!(
+            try_generate_frequent_itemsets_from_source(
+                &transactions(),
+                SupportFraction(0.4),
+                Some(3),
+                &cancelled
+            ),
+            Err(AprioriError::Cancelled)
+        );
+    }
+
+    #[test]
+    fn test_file_source() {
+        let path = std::env::temp_dir().join(format!(
+            "apriori-test-file-source-{}.bin",
+            std::process::id()
+        ));
+        let source = FileTransactions::create(&path, transactions()).unwrap();
+
+        let mut read_back = vec![];
+        source
+            .for_each(&mut |transaction| read_back.push(transaction.to_vec()))
+            .unwrap();
+        assert_eq!(read_back, transactions());
+
+        let frequent_itemsets =
+            generate_frequent_itemsets_from_source(&source, SupportFraction(0.4), 3).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(frequent_itemsets, expected(SupportFraction(0.4), 3));
+    }
+
+    #[test]
+    fn test_encode_to_file() {
+        use crate::itemsets::count::generate_frequent_itemsets;
+
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["bread", "milk", "cheese"],
+            vec!["bread", "milk"],
+            vec!["milk", "cheese", "bread"],
+            vec!["milk", "cheese", "yoghurt"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+        let path = std::env::temp_dir().join(format!(
+            "apriori-test-encode-to-file-{}.bin",
+            std::process::id()
+        ));
+
+        let (item_counts, inventory, source) =
+            encode_to_file(raw_transactions.clone(), &path).unwrap();
+        let frequent_itemsets =
+            generate_frequent_itemsets_from_source(&source, SupportFraction(0.5), 3).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let (expected_itemsets, expected_inventory) =
+            generate_frequent_itemsets(raw_transactions, SupportFraction(0.5), 3);
+        assert_eq!(inventory, expected_inventory);
+        assert_eq!(item_counts.len(), 4);
+        assert_eq!(frequent_itemsets, expected_itemsets);
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let source = FileTransactions::open("/nonexistent/apriori-transactions.bin");
+        assert!(generate_frequent_itemsets_from_source(&source, SupportFraction(0.4), 3).is_err());
+        assert!(matches!(
+            try_generate_frequent_itemsets_from_source(
+                &source,
+                SupportFraction(0.4),
+                Some(3),
+                &MiningOptions::default()
+            ),
+            Err(AprioriError::Io {
+                kind: io::ErrorKind::NotFound,
+                ..
+            })
+        ));
+    }
+}
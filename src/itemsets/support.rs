@@ -0,0 +1,201 @@
+#![allow(non_snake_case)]
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+#[cfg(feature = "python")]
+use pyo3::{FromPyObject, PyAny, PyResult};
+
+use crate::{
+    itemsets::options::RoundingMode,
+    types::{FrequentItemsets, HashState, Itemset, ItemsetLength},
+};
+
+/// A support given as a number of transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct SupportCount(pub u32);
+
+/// A support given as a fraction of transactions, normally in [0, 1].
+///
+/// Keeping the two apart in signatures means a count cannot be passed where a
+/// fraction is expected, or the other way round, without an explicit conversion.
+///
+/// An `f32` holds a fraction to about seven significant digits, and it is
+/// read back as the shortest decimal with that `f32` value before being
+/// multiplied by N in `f64`. So `SupportFraction(0.0001)` over 50_000_001
+/// transactions asks for 5000.0001 of them, and for 5001 once rounded up,
+/// however many transactions there are.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct SupportFraction(pub f32);
+
+#[cfg(feature = "python")]
+impl<'source> FromPyObject<'source> for SupportFraction {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        ob.extract().map(SupportFraction)
+    }
+}
+
+impl SupportCount {
+    /// This count as a fraction of `N` transactions.
+    pub fn to_fraction(self, N: usize) -> SupportFraction {
+        SupportFraction(self.0 as f32 / N as f32)
+    }
+}
+
+impl SupportFraction {
+    /// The number of transactions out of `N` this fraction asks for, rounded
+    /// as `rounding` says.
+    pub fn to_count(self, N: usize, rounding: RoundingMode) -> SupportCount {
+        let count = self.support_count(N, rounding);
+        SupportCount(u32::try_from(count).unwrap_or(u32::MAX))
+    }
+
+    /// [`to_count`](Self::to_count) as a plain `usize`, for the counting code.
+    pub(crate) fn support_count(self, N: usize, rounding: RoundingMode) -> usize {
+        rounding.support_count(self.to_decimal(), N)
+    }
+
+    /// The fraction as the shortest decimal that reads back as the same
+    /// `f32`, e.g. 0.0001 rather than the nearest `f32` to it,
+    /// 0.000099999997.
+    ///
+    /// That decimal is what was written, so its product with N in `f64` is
+    /// the threshold that was asked for: the `f32` error cannot push 0.4 * 5
+    /// past 2, nor hide the extra 0.0001 of 0.0001 * 50_000_001.
+    fn to_decimal(self) -> f64 {
+        self.0
+            .to_string()
+            .parse()
+            .unwrap_or_else(|_| f64::from(self.0))
+    }
+}
+
+/// Unit in which supports are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SupportScale {
+    /// Number of transactions containing the itemset.
+    Count,
+    /// Fraction of transactions, in [0, 1].
+    Fraction,
+    /// Occurrences per 1000 transactions.
+    PerMille,
+    /// Percentage of transactions, in [0, 100].
+    Percent,
+}
+
+impl SupportScale {
+    /// Express a support count out of `N` transactions in this scale.
+    pub fn apply(self, count: u32, N: usize) -> f32 {
+        let count = count as f32;
+        let N = N as f32;
+        match self {
+            SupportScale::Count => count,
+            SupportScale::Fraction => count / N,
+            SupportScale::PerMille => count / N * 1000.0,
+            SupportScale::Percent => count / N * 100.0,
+        }
+    }
+}
+
+/// Supports of every frequent itemset expressed in `scale`, for reporting.
+/// `N` is the number of transactions the itemsets were mined from.
+pub fn scale_supports(
+    itemsets: &FrequentItemsets,
+    N: usize,
+    scale: SupportScale,
+) -> HashMap<ItemsetLength, HashMap<Itemset, f32, HashState>, HashState> {
+    itemsets
+        .iter()
+        .map(|(&size, itemset_counts)| {
+            let scaled = itemset_counts
+                .iter()
+                .map(|(itemset, &count)| (itemset.clone(), scale.apply(count, N)))
+                .collect();
+            (size, scaled)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_support_conversions() {
+        assert_eq!(SupportCount(5).to_fraction(200), SupportFraction(0.025));
+        assert_eq!(
+            SupportFraction(0.3).to_count(4, RoundingMode::Ceil),
+            SupportCount(2)
+        );
+        assert_eq!(
+            SupportFraction(0.3).to_count(4, RoundingMode::Floor),
+            SupportCount(1)
+        );
+        assert_eq!(
+            SupportFraction(0.07).to_count(100, RoundingMode::default()),
+            SupportCount(7)
+        );
+        // 0.4_f32 is just above 0.4
+        assert_eq!(
+            SupportFraction(0.4).to_count(5, RoundingMode::Ceil),
+            SupportCount(2)
+        );
+        assert_eq!(
+            SupportCount(1).to_fraction(3).to_count(3, RoundingMode::Ceil),
+            SupportCount(1)
+        );
+    }
+
+    #[test]
+    fn test_support_conversions_large_n() {
+        // past a few million transactions the f32 error of the fraction is
+        // worth more than half a transaction, which must not turn Ceil and
+        // Floor into Round
+        assert_eq!(
+            SupportFraction(0.2).to_count(25_000_002, RoundingMode::Ceil),
+            SupportCount(5_000_001)
+        );
+        assert_eq!(
+            SupportFraction(0.5).to_count(10_000_001, RoundingMode::Floor),
+            SupportCount(5_000_000)
+        );
+        assert_eq!(
+            SupportFraction(0.5).to_count(10_000_001, RoundingMode::Ceil),
+            SupportCount(5_000_001)
+        );
+        assert_eq!(
+            SupportFraction(0.0001).to_count(50_000_001, RoundingMode::Ceil),
+            SupportCount(5001)
+        );
+        assert_eq!(
+            SupportFraction(0.0001).to_count(50_000_000, RoundingMode::Ceil),
+            SupportCount(5000)
+        );
+    }
+
+    #[test]
+    fn test_apply_scales() {
+        assert_eq!(SupportScale::Count.apply(5, 200), 5.0);
+        assert_eq!(SupportScale::Fraction.apply(5, 200), 0.025);
+        assert_eq!(SupportScale::PerMille.apply(5, 200), 25.0);
+        assert_eq!(SupportScale::Percent.apply(5, 200), 2.5);
+    }
+
+    #[test]
+    fn test_scale_supports() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! { itemset![0] => 3, itemset![1] => 2 },
+            2 => hashmap! { itemset![0, 1] => 1 },
+        };
+
+        let scaled = scale_supports(&itemsets, 4, SupportScale::Percent);
+
+        assert_eq!(
+            scaled,
+            hashmap! {
+                1 => hashmap! { itemset![0] => 75.0, itemset![1] => 50.0 },
+                2 => hashmap! { itemset![0, 1] => 25.0 },
+            }
+        );
+    }
+}
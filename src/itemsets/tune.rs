@@ -0,0 +1,132 @@
+use crate::{
+    itemsets::{
+        count::{count_items, mine_levels},
+        options::MiningOptions,
+        query::count_all_itemsets,
+        support::{SupportCount, SupportFraction},
+    },
+    types::{ItemCounts, ItemsetLength, RawTransaction, Transaction},
+};
+
+/// Suggest the highest `min_support` that still yields at least `target_count`
+/// frequent itemsets (summed over all levels up to `k`).
+///
+/// Transactions are encoded once, then the support count threshold is
+/// binary-searched, re-mining at every step: expect about log2(N) mining runs,
+/// each no more expensive than mining at the returned threshold.
+/// The search stops once the bracket is narrower than `tolerance` (as a support
+/// fraction); a tolerance of 0 gives the exact answer.
+///
+/// If even a threshold of a single transaction gives fewer than `target_count`
+/// itemsets, `1 / N` is returned.
+pub fn suggest_min_support(
+    raw_transactions: Vec<RawTransaction>,
+    k: ItemsetLength,
+    target_count: usize,
+    tolerance: f64,
+) -> SupportFraction {
+    let N = raw_transactions.len();
+    if N == 0 {
+        return SupportFraction(0.0);
+    }
+    if target_count == 0 {
+        return SupportFraction(1.0);
+    }
+
+    let (item_counts, _, transactions) = count_items(raw_transactions);
+    let num_itemsets = |min_support_count: usize| {
+        count_frequent_itemsets(&item_counts, &transactions, min_support_count, k)
+    };
+
+    // invariant: `lo` yields at least `target_count` itemsets, `hi` yields fewer
+    let mut lo = 1;
+    let mut hi = N + 1;
+    if num_itemsets(lo) < target_count {
+        return SupportCount(lo as u32).to_fraction(N);
+    }
+
+    while hi - lo > 1 && (hi - lo) as f64 / N as f64 > tolerance {
+        let mid = lo + (hi - lo) / 2;
+        if num_itemsets(mid) >= target_count {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    SupportCount(lo as u32).to_fraction(N)
+}
+
+fn count_frequent_itemsets(
+    item_counts: &ItemCounts,
+    transactions: &[Transaction],
+    min_support_count: usize,
+    k: ItemsetLength,
+) -> usize {
+    let mut item_counts = item_counts.clone();
+    item_counts.retain(|_, &mut count| count as usize >= min_support_count);
+
+    let itemsets = mine_levels(
+        item_counts,
+        transactions.to_vec(),
+        min_support_count,
+        Some(k),
+        &MiningOptions::default(),
+    )
+    .expect("default options impose no limits");
+
+    count_all_itemsets(&itemsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::count::generate_frequent_itemsets;
+
+    fn transactions() -> Vec<RawTransaction<'static>> {
+        vec![
+            vec!["bread", "milk", "cheese"],
+            vec!["bread", "milk"],
+            vec!["milk", "cheese", "bread"],
+            vec!["milk", "cheese", "bread"],
+            vec!["milk", "cheese", "yoghurt"],
+            vec!["milk", "bread"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect::<RawTransaction>())
+        .collect()
+    }
+
+    fn num_itemsets(min_support: SupportFraction) -> usize {
+        let (frequent_itemsets, _) = generate_frequent_itemsets(transactions(), min_support, 3);
+        count_all_itemsets(&frequent_itemsets)
+    }
+
+    #[test]
+    fn test_suggest_min_support() {
+        for target_count in 1..=10 {
+            let min_support = suggest_min_support(transactions(), 3, target_count, 0.0);
+
+            assert!(num_itemsets(min_support) >= target_count);
+            // the next count up gives too few itemsets
+            if min_support < SupportFraction(1.0) {
+                assert!(num_itemsets(SupportFraction(min_support.0 + 1.0 / 6.0)) < target_count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_suggest_min_support_unreachable_target() {
+        let min_support = suggest_min_support(transactions(), 3, 1000, 0.0);
+        assert!((min_support.0 - 1.0 / 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_suggest_min_support_tolerance() {
+        let exact = suggest_min_support(transactions(), 3, 5, 0.0);
+        let approx = suggest_min_support(transactions(), 3, 5, 0.5);
+
+        assert!(approx <= exact);
+        assert!(num_itemsets(approx) >= 5);
+    }
+}
@@ -0,0 +1,18 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Modulus of the hash family, the Mersenne prime 2^61 - 1.
+const PRIME: u64 = (1 << 61) - 1;
+
+/// Parameters `(a, b)` of `n` random members of the family. The same `seed`
+/// always draws the same functions.
+pub(crate) fn draw_universal_hashes(n: usize, seed: u64) -> Vec<(u64, u64)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| (rng.gen_range(1..PRIME), rng.gen_range(0..PRIME)))
+        .collect()
+}
+
+/// `(a * x + b) mod PRIME`, one member of a universal hash family.
+pub(crate) fn universal_hash(a: u64, b: u64, x: u64) -> u64 {
+    ((u128::from(a) * u128::from(x) + u128::from(b)) % u128::from(PRIME)) as u64
+}
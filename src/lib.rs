@@ -1,16 +1,36 @@
 #![allow(dead_code,non_snake_case)]
+#[cfg(test)]
+#[macro_use]
+mod test_macros;
+pub mod columnar;
+pub mod error;
+pub mod inventory;
 pub mod itemsets;
+pub mod metrics;
+pub mod preprocess;
+pub mod reader;
+pub mod result;
 pub mod rules;
 pub mod types;
+pub mod writer;
+#[cfg(feature = "python")]
 mod wrapper;
 
+#[cfg(feature = "python")]
 use itemsets::count::__pyo3_get_function_generate_frequent_1_itemset_counts;
+#[cfg(feature = "python")]
+use itemsets::support::SupportFraction;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
+#[cfg(feature = "python")]
 use pyo3::wrap_pyfunction;
+#[cfg(feature = "python")]
 use pyo3::{prelude::*, PyObjectProtocol};
 use std::collections::{HashMap, HashSet};
+#[cfg(feature = "python")]
 use types::{Inventory, PyFrequentItemsets, PyItemName, RawTransaction, RawTransactionId};
 
+#[cfg(feature = "python")]
 fn main() {
     #[pymodule]
     fn apriori(_: Python, m: &PyModule) -> PyResult<()> {
@@ -33,11 +53,12 @@ fn main() {
 ///
 /// Returns:
 ///     A tuple of (i) a list of association rules and (ii) frequent itemsets by size.
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(text_signature = "(transactions, min_support, min_confidence, max_length, /)")]
 fn apriori(
     raw_transactions: Vec<RawTransaction>,
-    min_support: f32,
+    min_support: SupportFraction,
     min_confidence: f32,
     max_length: usize,
 ) -> (Vec<Rule>, PyFrequentItemsets) {
@@ -62,11 +83,12 @@ fn apriori(
 ///
 /// Returns:
 ///     A tuple of (i) frequent itemsets by size and (ii) a dictionary mapping of item ID to item name.
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(text_signature = "(transactions, min_support, max_length, /)")]
 fn generate_frequent_itemsets(
     raw_transactions: Vec<RawTransaction>,
-    min_support: f32,
+    min_support: SupportFraction,
     max_length: usize,
 ) -> (PyFrequentItemsets, Inventory) {
     let (itemset_counts, inventory) =
@@ -84,11 +106,12 @@ fn generate_frequent_itemsets(
 ///
 /// Returns:
 ///     Frequent itemsets by size.
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(text_signature = "(transactions, min_support, max_length, /)")]
 fn generate_frequent_itemsets_id(
     raw_transactions: Vec<RawTransactionId>,
-    min_support: f32,
+    min_support: SupportFraction,
     max_length: usize,
 ) -> Py<PyDict> {
     let itemset_counts =
@@ -97,6 +120,7 @@ fn generate_frequent_itemsets_id(
     wrapper::convert_itemset_counts(itemset_counts)
 }
 
+#[cfg(feature = "python")]
 #[pyclass]
 pub struct Rule {
     #[pyo3(get)]
@@ -107,8 +131,13 @@ pub struct Rule {
     confidence: f32,
     #[pyo3(get)]
     lift: f32,
+    #[pyo3(get)]
+    antecedent_support: u32,
+    #[pyo3(get)]
+    union_support: u32,
 }
 
+#[cfg(feature = "python")]
 #[pyproto]
 impl PyObjectProtocol for Rule {
     fn __repr__(&self) -> PyResult<String> {
@@ -0,0 +1,655 @@
+#![allow(non_snake_case)]
+
+use crate::{
+    itemsets::support::SupportCount,
+    rules::rule::Rule,
+    types::{FrequentItemsets, HashState, Inventory, ItemId, ItemName, Itemset},
+};
+use std::collections::HashMap;
+
+/// Number of the `N` transactions that do not contain an itemset appearing in
+/// `count` of them, the absent cells of the contingency tables below.
+///
+/// A count above `N` cannot come from mining `N` transactions; it is a bug in
+/// debug builds and gives 0 otherwise, rather than wrapping around. Beyond
+/// `u32::MAX` transactions the complement saturates at `u32::MAX`.
+pub fn complement_support(count: SupportCount, N: usize) -> SupportCount {
+    debug_assert!(
+        count.0 as usize <= N,
+        "support count {} exceeds the {} transactions",
+        count.0,
+        N
+    );
+    let complement = N.saturating_sub(count.0 as usize);
+    SupportCount(complement.min(u32::MAX as usize) as u32)
+}
+
+/// Number of the `N` transactions holding neither item of a pair, from the
+/// item counts `a` and `b` and the pair's count `both`.
+///
+/// Counted in u64, so `a + b` cannot overflow, and saturating at 0 on counts
+/// that no `N` transactions could produce.
+fn neither_count(a: u32, b: u32, both: u32, N: usize) -> u64 {
+    let either = (u64::from(a) + u64::from(b)).saturating_sub(u64::from(both));
+    (N as u64).saturating_sub(either)
+}
+
+/// Collective strength of every frequent 2-itemset.
+///
+/// For the pair `{a, b}` this compares how often a transaction agrees on both
+/// items (contains both, or neither) against the agreement expected under
+/// independence, and does the same for the violations (contains exactly one):
+///
+/// ```text
+/// CS = (P(ab) + P(¬a¬b)) / (P(a)P(b) + P(¬a)P(¬b))
+///    × (1 - P(a)P(b) - P(¬a)P(¬b)) / (1 - P(ab) - P(¬a¬b))
+/// ```
+///
+/// A value of 1 means independence, above 1 a positive association. Pairs
+/// that are never violated get `f32::INFINITY`.
+pub fn collective_strength(
+    itemsets: &FrequentItemsets,
+    N: usize,
+) -> HashMap<Itemset, f32, HashState> {
+    let (item_counts, pair_counts) = match (itemsets.get(&1), itemsets.get(&2)) {
+        (Some(item_counts), Some(pair_counts)) => (item_counts, pair_counts),
+        _ => return HashMap::default(),
+    };
+    let fraction = |count: u64| count as f32 / N as f32;
+
+    pair_counts
+        .iter()
+        .filter_map(|(pair, &count)| {
+            let a = *item_counts.get(&[pair[0]][..])?;
+            let b = *item_counts.get(&[pair[1]][..])?;
+            let (p_a, p_b, p_ab) = (
+                fraction(a.into()),
+                fraction(b.into()),
+                fraction(count.into()),
+            );
+            let p_not_a = fraction(complement_support(SupportCount(a), N).0.into());
+            let p_not_b = fraction(complement_support(SupportCount(b), N).0.into());
+            let p_neither = fraction(neither_count(a, b, count, N));
+
+            let agreement = p_ab + p_neither;
+            let expected_agreement = p_a * p_b + p_not_a * p_not_b;
+            let violation = 1.0 - agreement;
+
+            let strength = if violation <= 0.0 {
+                f32::INFINITY
+            } else {
+                (agreement * (1.0 - expected_agreement)) / (expected_agreement * violation)
+            };
+            Some((pair.clone(), strength))
+        })
+        .collect()
+}
+
+/// Number of transactions `itemset` would be expected to appear in if all of
+/// its items occurred independently: `N * P(a) * P(b) * ...`, using the stored
+/// 1-itemset supports. Returns `None` if any item is not frequent.
+pub fn expected_support(itemset: &[ItemId], itemsets: &FrequentItemsets, N: usize) -> Option<f32> {
+    let item_counts = itemsets.get(&1)?;
+    let N = N as f32;
+
+    itemset.iter().try_fold(N, |expected, &item| {
+        let count = *item_counts.get(&[item][..])?;
+        Some(expected * count as f32 / N)
+    })
+}
+
+/// Lift of two disjoint itemsets occurring together over `N` transactions:
+///
+/// ```text
+/// lift = P(left ∪ right) / (P(left) * P(right))
+/// ```
+///
+/// For two single items this is the lift of the pair, and for any split the
+/// lift of the rule `left -> right`. Returns `None` if the itemsets share an
+/// item, or if their union, and so `left` or `right`, is not in `itemsets`.
+pub fn itemset_lift(
+    left: &[ItemId],
+    right: &[ItemId],
+    itemsets: &FrequentItemsets,
+    N: usize,
+) -> Option<f32> {
+    let mut union: Itemset = left.iter().chain(right).copied().collect();
+    union.sort_unstable();
+    union.dedup();
+    if union.len() != left.len() + right.len() {
+        return None;
+    }
+
+    let support = |itemset: &[ItemId]| -> Option<f32> {
+        let mut itemset = itemset.to_vec();
+        itemset.sort_unstable();
+        let count = *itemsets.get(&itemset.len())?.get(itemset.as_slice())?;
+        Some(count as f32)
+    };
+    let union_count = support(&union)?;
+    Some(union_count * N as f32 / (support(left)? * support(right)?))
+}
+
+/// Surprise of every frequent itemset of two or more items, in bits:
+///
+/// ```text
+/// surprise = log2(observed support / expected support)
+/// ```
+///
+/// with the expected support from [`expected_support`]. 0 means the items
+/// co-occur as often as chance predicts, each bit above that doubles how often
+/// they are seen together relative to chance, and negative values mean they
+/// avoid each other. Itemsets with an item missing from level 1 are left out.
+pub fn surprise(itemsets: &FrequentItemsets, N: usize) -> HashMap<Itemset, f32, HashState> {
+    itemsets
+        .iter()
+        .filter(|(&size, _)| size > 1)
+        .flat_map(|(_, itemset_counts)| itemset_counts.iter())
+        .filter_map(|(itemset, &count)| {
+            let expected = expected_support(itemset, itemsets, N)?;
+            Some((itemset.clone(), (count as f32 / expected).log2()))
+        })
+        .collect()
+}
+
+/// Mutual information, in nats, between the presence of the two items of
+/// every frequent 2-itemset.
+///
+/// For the pair `{a, b}` this sums over the four cells of the 2x2 table of
+/// presence against absence, all recovered from the 1- and 2-itemset supports:
+///
+/// ```text
+/// MI = Σ P(x, y) ln(P(x, y) / (P(x)P(y)))   for x in {a, ¬a}, y in {b, ¬b}
+/// ```
+///
+/// It is 0 exactly when the items are independent and positive otherwise,
+/// whether they attract or avoid each other. Empty cells contribute 0.
+pub fn pairwise_mutual_information(
+    itemsets: &FrequentItemsets,
+    N: usize,
+) -> HashMap<Itemset, f32, HashState> {
+    let (item_counts, pair_counts) = match (itemsets.get(&1), itemsets.get(&2)) {
+        (Some(item_counts), Some(pair_counts)) => (item_counts, pair_counts),
+        _ => return HashMap::default(),
+    };
+    let fraction = |count: u64| count as f32 / N as f32;
+
+    pair_counts
+        .iter()
+        .filter_map(|(pair, &count)| {
+            let a = *item_counts.get(&[pair[0]][..])?;
+            let b = *item_counts.get(&[pair[1]][..])?;
+            let (p_a, p_b) = (fraction(a.into()), fraction(b.into()));
+            let p_not_a = fraction(complement_support(SupportCount(a), N).0.into());
+            let p_not_b = fraction(complement_support(SupportCount(b), N).0.into());
+
+            let cells = [
+                (fraction(count.into()), p_a * p_b),
+                (fraction(a.saturating_sub(count).into()), p_a * p_not_b),
+                (fraction(b.saturating_sub(count).into()), p_not_a * p_b),
+                (fraction(neither_count(a, b, count, N)), p_not_a * p_not_b),
+            ];
+            let information = cells
+                .iter()
+                .filter(|&&(joint, _)| joint > 0.0)
+                .map(|&(joint, independent)| joint * (joint / independent).ln())
+                .sum();
+            Some((pair.clone(), information))
+        })
+        .collect()
+}
+
+/// Zhang's metric of a rule `A -> B`, in `[-1, 1]`.
+///
+/// ```text
+/// Zhang = (P(B|A) - P(B)) / max(P(B|A) * (1 - P(B)), P(B) * (1 - P(B|A)))
+/// ```
+///
+/// Positive values mean `A` makes `B` more likely, negative values less
+/// likely, with both directions scaled alike. The consequent's support is
+/// recovered from the rule as `confidence / lift`. Returns 0 when `B` occurs in
+/// every transaction, where the metric is undefined.
+pub fn zhang(rule: &Rule) -> f32 {
+    if rule.lift == 0.0 {
+        // `A` and `B` never co-occur
+        return -1.0;
+    }
+    let confidence = rule.confidence;
+    let p_consequent = confidence / rule.lift;
+
+    let denominator = (confidence * (1.0 - p_consequent)).max(p_consequent * (1.0 - confidence));
+    if denominator == 0.0 {
+        return 0.0;
+    }
+    (confidence - p_consequent) / denominator
+}
+
+/// One-sided p-value of a rule `A -> B` from Fisher's exact test on the 2x2
+/// table of antecedent presence against consequent presence over `N`
+/// transactions.
+///
+/// This is the probability, with the marginal supports of `A` and `B` held
+/// fixed and the two independent, of seeing them together in at least as many
+/// transactions as observed. Small values mean the rule is unlikely to be a
+/// chance co-occurrence, which a confidence cutoff cannot tell at low support.
+///
+/// The rule's supports must be filled in, as in rules from
+/// [`generate_rules`](crate::rules::search::generate_rules). Returns `None`
+/// when the consequent is not in `itemsets`, so its support is unknown.
+pub fn rule_pvalue(rule: &Rule, itemsets: &FrequentItemsets, N: usize) -> Option<f32> {
+    let consequent = rule.get_consequent();
+    let both = rule.union_support.0 as usize;
+    let antecedent = rule.antecedent_support.0 as usize;
+    let consequent_support = *itemsets.get(&consequent.len())?.get(consequent)?;
+    let consequent = consequent_support as usize;
+    let not_consequent = complement_support(SupportCount(consequent_support), N).0 as usize;
+
+    let ln_binomial = |n: usize, k: usize| ln_factorial(n) - ln_factorial(k) - ln_factorial(n - k);
+
+    // hypergeometric upper tail, over the co-occurrence counts the margins
+    // allow: at least `both`, and no fewer than `A` and `B` must share
+    let ln_total = ln_binomial(N, antecedent);
+    let lowest = both.max((antecedent + consequent).saturating_sub(N));
+    let pvalue: f64 = (lowest..=antecedent.min(consequent))
+        .map(|x| {
+            (ln_binomial(consequent, x) + ln_binomial(not_consequent, antecedent - x) - ln_total)
+                .exp()
+        })
+        .sum();
+    Some(pvalue.min(1.0) as f32)
+}
+
+/// ln(n!), from the Lanczos approximation of the gamma function, so that no
+/// table of factorials up to N has to be built.
+fn ln_factorial(n: usize) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    // ln Γ(n + 1)
+    let x = n as f64;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, &c)| sum + c / (x + i as f64 + 1.0));
+    let t = x + G + 0.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// Mean support count of each level as a fraction of the previous level's,
+/// as `(size, ratio)` in ascending size from size 2.
+///
+/// A ratio well below 1 means associations weaken quickly as itemsets grow, a
+/// ratio near 1 that the data is dense in them. Sizes whose own level or
+/// previous level is missing or empty have no ratio and are left out.
+pub fn support_decay(itemsets: &FrequentItemsets) -> Vec<(usize, f32)> {
+    let mean_support = |size: usize| {
+        itemsets
+            .get(&size)
+            .filter(|itemset_counts| !itemset_counts.is_empty())
+            .map(|itemset_counts| {
+                itemset_counts.values().map(|&count| f64::from(count)).sum::<f64>()
+                    / itemset_counts.len() as f64
+            })
+    };
+
+    let mut sizes: Vec<usize> = itemsets.keys().copied().filter(|&size| size > 1).collect();
+    sizes.sort_unstable();
+    sizes
+        .into_iter()
+        .filter_map(|size| {
+            let ratio = mean_support(size)? / mean_support(size - 1)?;
+            Some((size, ratio as f32))
+        })
+        .collect()
+}
+
+/// Support count of every frequent itemset scaled by the value of its items,
+/// for ranking patterns by business value rather than raw frequency.
+///
+/// An itemset's weight is the product of its items' weights, looked up by name
+/// through `inventory`, and items missing from `item_weights` weigh 1. With
+/// the product, weights act as multipliers: unweighted itemsets keep their
+/// plain count, and a weight of 2 doubles every itemset holding the item
+/// whatever its length. Summing the weights instead would favour longer
+/// itemsets for their length alone.
+pub fn weighted_support(
+    itemsets: &FrequentItemsets,
+    item_weights: &HashMap<ItemName, f32>,
+    inventory: &Inventory,
+) -> HashMap<Itemset, f32> {
+    let weight = |item_id: &ItemId| item_weights.get(inventory[item_id]).copied().unwrap_or(1.0);
+
+    itemsets
+        .values()
+        .flat_map(|itemset_counts| itemset_counts.iter())
+        .map(|(itemset, &count)| {
+            let itemset_weight: f32 = itemset.iter().map(weight).product();
+            (itemset.clone(), count as f32 * itemset_weight)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complement_support() {
+        assert_eq!(complement_support(SupportCount(3), 10), SupportCount(7));
+        assert_eq!(complement_support(SupportCount(0), 10), SupportCount(10));
+        assert_eq!(complement_support(SupportCount(10), 10), SupportCount(0));
+        assert_eq!(complement_support(SupportCount(0), 0), SupportCount(0));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "support count 11 exceeds the 10 transactions")]
+    fn test_complement_support_above_n() {
+        complement_support(SupportCount(11), 10);
+    }
+
+    #[test]
+    fn test_collective_strength() {
+        // Tea/coffee example from Tan, Kumar & Srivastava (2002): of 100
+        // transactions, 20 contain tea, 90 coffee and 15 both.
+        let itemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 20,
+                itemset![1] => 90,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 15,
+            },
+        };
+
+        let strengths = collective_strength(&itemsets, 100);
+
+        assert!((strengths[&itemset![0, 1]] - 0.7115).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_collective_strength_never_violated() {
+        let itemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 2,
+                itemset![1] => 2,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 2,
+            },
+        };
+
+        let strengths = collective_strength(&itemsets, 4);
+
+        assert_eq!(strengths[&itemset![0, 1]], f32::INFINITY);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_pair_metrics_past_u32() {
+        // 5 billion transactions: a + b overflows u32, but the table is the
+        // same as 3, 3 and 2 of 5
+        let pair = |a: u32, b: u32, both: u32| -> FrequentItemsets {
+            hashmap! {
+                1 => hashmap! { itemset![0] => a, itemset![1] => b },
+                2 => hashmap! { itemset![0, 1] => both },
+            }
+        };
+        let large = pair(3_000_000_000, 3_000_000_000, 2_000_000_000);
+        let small = pair(3, 3, 2);
+        let N = 5_000_000_000;
+
+        assert_eq!(
+            neither_count(3_000_000_000, 3_000_000_000, 2_000_000_000, N),
+            1_000_000_000
+        );
+        assert_eq!(
+            complement_support(SupportCount(0), N),
+            SupportCount(u32::MAX)
+        );
+
+        let strength = collective_strength(&large, N)[&itemset![0, 1]];
+        assert!((strength - collective_strength(&small, 5)[&itemset![0, 1]]).abs() < 1e-4);
+        let information = pairwise_mutual_information(&large, N)[&itemset![0, 1]];
+        assert!(
+            (information - pairwise_mutual_information(&small, 5)[&itemset![0, 1]]).abs() < 1e-4
+        );
+    }
+
+    #[test]
+    fn test_expected_support() {
+        let itemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 5,
+                itemset![1] => 4,
+                itemset![2] => 2,
+            },
+        };
+
+        // 10 * 0.5 * 0.4 = 2, and 2 * 0.2 = 0.4
+        assert!((expected_support(&[0, 1], &itemsets, 10).unwrap() - 2.0).abs() < 1e-6);
+        assert!((expected_support(&[0, 1, 2], &itemsets, 10).unwrap() - 0.4).abs() < 1e-6);
+        assert_eq!(expected_support(&[0, 3], &itemsets, 10), None);
+    }
+
+    #[test]
+    fn test_itemset_lift() {
+        let itemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 6,
+                itemset![1] => 5,
+                itemset![2] => 4,
+                itemset![3] => 5,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 4,
+                itemset![2, 3] => 4,
+                itemset![0, 2] => 3,
+            },
+            4 => hashmap! {
+                itemset![0, 1, 2, 3] => 3,
+            },
+        };
+
+        // 4 * 10 / (6 * 5), the same as the rule {0} -> {1}
+        let pair_lift = itemset_lift(&[0], &[1], &itemsets, 10).unwrap();
+        assert!((pair_lift - 4.0 / 3.0).abs() < 1e-6);
+        let mut rule = rule(0.0, 0.0);
+        rule.compute_confidence(&itemsets, 4, 10.0);
+        assert!((pair_lift - rule.lift).abs() < 1e-6);
+
+        // 3 * 10 / (4 * 4), in either order
+        let lift = itemset_lift(&[1, 0], &[2, 3], &itemsets, 10).unwrap();
+        assert!((lift - 1.875).abs() < 1e-6);
+        assert_eq!(itemset_lift(&[2, 3], &[0, 1], &itemsets, 10), Some(lift));
+
+        // {1, 2} is not frequent
+        assert_eq!(itemset_lift(&[1], &[2], &itemsets, 10), None);
+        // overlapping
+        assert_eq!(itemset_lift(&[0, 1], &[1], &itemsets, 10), None);
+    }
+
+    #[test]
+    fn test_surprise() {
+        let itemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 50,
+                itemset![1] => 40,
+                itemset![2] => 20,
+            },
+            2 => hashmap! {
+                // expected 100 * 0.5 * 0.4 = 20
+                itemset![0, 1] => 21,
+                // expected 100 * 0.5 * 0.2 = 10
+                itemset![0, 2] => 40,
+                // expected 100 * 0.4 * 0.2 = 8
+                itemset![1, 2] => 2,
+            },
+            3 => hashmap! {
+                // expected 100 * 0.5 * 0.4 * 0.2 = 4
+                itemset![0, 1, 2] => 2,
+            },
+        };
+
+        let surprise = surprise(&itemsets, 100);
+
+        assert_eq!(surprise.len(), 4);
+        // close to independence
+        assert!(surprise[&itemset![0, 1]].abs() < 0.1);
+        assert!((surprise[&itemset![0, 2]] - 2.0).abs() < 1e-6);
+        assert!((surprise[&itemset![1, 2]] + 2.0).abs() < 1e-6);
+        assert!((surprise[&itemset![0, 1, 2]] + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_pairwise_mutual_information() {
+        // 100 transactions, 20 contain tea, 90 coffee and 15 both
+        // MI = 0.15 ln(0.15 / 0.18) + 0.05 ln(0.05 / 0.02)
+        //    + 0.75 ln(0.75 / 0.72) + 0.05 ln(0.05 / 0.08) = 0.025583
+        let itemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 20,
+                itemset![1] => 90,
+                itemset![2] => 50,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 15,
+                // independent: 100 * 0.2 * 0.5 = 10
+                itemset![0, 2] => 10,
+            },
+        };
+
+        let information = pairwise_mutual_information(&itemsets, 100);
+
+        assert_eq!(information.len(), 2);
+        assert!((information[&itemset![0, 1]] - 0.025583).abs() < 1e-5);
+        assert!(information[&itemset![0, 2]].abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rule_pvalue() {
+        // Fisher's lady tasting tea: of 8 cups, 4 had milk poured first, she
+        // picked 4 as such and got 3 right. The published one-sided p-value is
+        // 17/70.
+        let itemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 4,
+                itemset![1] => 4,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 3,
+            },
+        };
+        let mut tea = rule(0.75, 1.5);
+        tea.antecedent_support = SupportCount(4);
+        tea.union_support = SupportCount(3);
+
+        assert!((rule_pvalue(&tea, &itemsets, 8).unwrap() - 17.0 / 70.0).abs() < 1e-6);
+
+        // all 4 right: 1/70
+        tea.union_support = SupportCount(4);
+        assert!((rule_pvalue(&tea, &itemsets, 8).unwrap() - 1.0 / 70.0).abs() < 1e-6);
+
+        // every table has at least 0 co-occurrences
+        tea.union_support = SupportCount(0);
+        assert!((rule_pvalue(&tea, &itemsets, 8).unwrap() - 1.0).abs() < 1e-6);
+
+        // the consequent's support is unknown
+        tea.combi = vec![0, 2];
+        assert_eq!(rule_pvalue(&tea, &itemsets, 8), None);
+    }
+
+    fn rule(confidence: f32, lift: f32) -> Rule {
+        Rule {
+            split: 1,
+            combi: vec![0, 1],
+            confidence,
+            lift,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
+        }
+    }
+
+    #[test]
+    fn test_zhang() {
+        // P(A) = 0.4, P(B) = 0.5, P(AB) = 0.3: confidence 0.75, lift 1.5
+        assert!((zhang(&rule(0.75, 1.5)) - 2.0 / 3.0).abs() < 1e-6);
+
+        // P(A) = 0.5, P(B) = 0.6, P(AB) = 0.1: confidence 0.2, lift 1/3
+        assert!((zhang(&rule(0.2, 1.0 / 3.0)) + 5.0 / 6.0).abs() < 1e-6);
+
+        // independence
+        assert!(zhang(&rule(0.5, 1.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_support_decay() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 8,
+                itemset![1] => 6,
+                itemset![2] => 4,
+                itemset![3] => 6,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 4,
+                itemset![0, 3] => 2,
+            },
+            3 => hashmap! {
+                itemset![0, 1, 3] => 1,
+            },
+            4 => hashmap! {},
+        };
+
+        // means 6, 3 and 1; the empty level has no ratio
+        assert_eq!(support_decay(&itemsets), vec![(2, 0.5), (3, 1.0 / 3.0)]);
+        assert_eq!(
+            support_decay(&hashmap! { 1 => hashmap! { itemset![0] => 8 } }),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_weighted_support() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! { itemset![0] => 8, itemset![1] => 6, itemset![2] => 2 },
+            2 => hashmap! { itemset![0, 1] => 5, itemset![0, 2] => 2 },
+        };
+        let inventory: Inventory = hashmap! { 0 => "bread", 1 => "milk", 2 => "caviar" };
+        let item_weights: HashMap<ItemName, f32> = hashmap! { "caviar" => 4.0, "milk" => 0.5 };
+
+        let weighted = weighted_support(&itemsets, &item_weights, &inventory);
+
+        assert_eq!(
+            weighted,
+            hashmap! {
+                itemset![0] => 8.0,
+                itemset![1] => 3.0,
+                itemset![2] => 8.0,
+                itemset![0, 1] => 2.5,
+                itemset![0, 2] => 8.0,
+            }
+        );
+        // the rarest pair ranks first once caviar's value is counted
+        let top_pair = weighted
+            .iter()
+            .filter(|(itemset, _)| itemset.len() == 2)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(itemset, _)| itemset.clone());
+        assert_eq!(top_pair, Some(itemset![0, 2]));
+        assert_eq!(
+            weighted_support(&itemsets, &HashMap::new(), &inventory)[&itemset![0, 1]],
+            5.0
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+
+use crate::types::{HashState, ItemCounts, ItemId, RawTransaction, Transaction};
+
+/// Keep only the transactions for which `predicate` holds, e.g. to drop
+/// synthetic or test baskets before mining.
+///
+/// This works on raw transactions, before anything is encoded or counted, so
+/// a dropped transaction counts towards neither any itemset's support nor N,
+/// and its items get no id unless they occur elsewhere.
+pub fn filter_transactions<'l, P>(
+    mut raw_transactions: Vec<RawTransaction<'l>>,
+    predicate: P,
+) -> Vec<RawTransaction<'l>>
+where
+    P: Fn(&RawTransaction<'l>) -> bool,
+{
+    raw_transactions.retain(|transaction| predicate(transaction));
+    raw_transactions
+}
+
+/// Indices of the transactions with more than `max_len` items.
+///
+/// Counting cost grows combinatorially with basket size, so a handful of very
+/// large transactions can dominate a run. Use this to find them before mining.
+pub fn oversized_transactions<T>(
+    raw_transactions: &[HashSet<T, HashState>],
+    max_len: usize,
+) -> Vec<usize> {
+    raw_transactions
+        .iter()
+        .enumerate()
+        .filter(|(_, transaction)| transaction.len() > max_len)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Items present in every one of the `N` transactions, in ascending id order.
+///
+/// Such an item can be added to any frequent itemset without changing its
+/// support, so it doubles the output without adding information, and usually
+/// points to an artifact of the data, e.g. a store id on every basket. This
+/// only reads the level-1 counts; drop the items from the raw transactions
+/// and mine again to exclude them.
+pub fn constant_items(item_counts: &ItemCounts, N: usize) -> Vec<ItemId> {
+    let mut items: Vec<ItemId> = item_counts
+        .iter()
+        .filter(|&(_, &count)| N > 0 && count as usize == N)
+        .map(|(&item, _)| item)
+        .collect();
+    items.sort_unstable();
+    items
+}
+
+/// Cap every transaction at `max_len` items, keeping its items with the
+/// highest support in `item_counts` (ties go to the lower id).
+///
+/// This is an approximation: itemsets involving a dropped item lose the
+/// occurrences from truncated transactions, so their counts, and whether they
+/// are found at all, may fall short of the exact result. Level-1 counts are
+/// not affected, being computed beforehand. Transactions stay sorted.
+pub fn truncate_transactions(
+    transactions: &mut [Transaction],
+    item_counts: &ItemCounts,
+    max_len: usize,
+) {
+    for transaction in transactions.iter_mut() {
+        truncate_transaction(transaction, item_counts, max_len);
+    }
+}
+
+/// [`truncate_transactions`] for a single transaction.
+pub(crate) fn truncate_transaction(
+    transaction: &mut Transaction,
+    item_counts: &ItemCounts,
+    max_len: usize,
+) {
+    if transaction.len() <= max_len {
+        return;
+    }
+    let support = |item: &usize| item_counts.get(item).copied().unwrap_or(0);
+
+    transaction.sort_unstable_by(|a, b| support(b).cmp(&support(a)).then(a.cmp(b)));
+    transaction.truncate(max_len);
+    transaction.sort_unstable();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::support::SupportFraction;
+
+    #[test]
+    fn test_oversized_transactions() {
+        let large: Vec<String> = (0..50).map(|i| i.to_string()).collect();
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["a", "b"],
+            large.iter().map(String::as_str).collect(),
+            vec!["a", "b", "c"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        assert_eq!(oversized_transactions(&raw_transactions, 10), vec![1]);
+        assert_eq!(oversized_transactions(&raw_transactions, 2), vec![1, 2]);
+        assert!(oversized_transactions(&raw_transactions, 50).is_empty());
+    }
+
+    #[test]
+    fn test_constant_items() {
+        use crate::itemsets::count::generate_frequent_1_itemset_counts;
+
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["store-42", "bread", "milk"],
+            vec!["store-42", "bread"],
+            vec!["store-42", "milk", "jam"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        let (item_counts, inventory, _) =
+            generate_frequent_1_itemset_counts(raw_transactions, SupportFraction(0.0));
+        let constant = constant_items(&item_counts, 3);
+
+        assert_eq!(constant.len(), 1);
+        assert_eq!(inventory[&constant[0]], "store-42");
+        assert!(constant_items(&item_counts, 4).is_empty());
+        assert!(constant_items(&ItemCounts::default(), 0).is_empty());
+    }
+
+    #[test]
+    fn test_truncate_transactions() {
+        let item_counts: ItemCounts = hashmap! { 0 => 2, 1 => 9, 2 => 5, 3 => 7, 4 => 5 };
+        let mut transactions = vec![vec![0, 1, 2, 3, 4], vec![0, 2]];
+
+        truncate_transactions(&mut transactions, &item_counts, 3);
+
+        // 1 and 3 have the highest support, 2 wins the tie with 4
+        assert_eq!(transactions, vec![vec![1, 2, 3], vec![0, 2]]);
+    }
+
+    #[test]
+    fn test_filtered_transactions_do_not_count() {
+        use crate::itemsets::count::generate_frequent_itemsets;
+
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["bread", "milk"],
+            vec!["bread", "milk", "test-sku"],
+            vec!["bread", "cheese"],
+            vec!["test-sku", "cheese"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        let kept = filter_transactions(raw_transactions, |transaction| {
+            !transaction.contains("test-sku")
+        });
+        assert_eq!(kept.len(), 2);
+
+        // 0.5 of the 2 remaining transactions, not of all 4
+        let (itemsets, inventory) = generate_frequent_itemsets(kept, SupportFraction(0.5), 2);
+        let mut counts: Vec<(&str, u32)> = itemsets[&1]
+            .iter()
+            .map(|(itemset, &count)| (inventory[&itemset[0]], count))
+            .collect();
+        counts.sort_unstable();
+
+        assert_eq!(counts, vec![("bread", 2), ("cheese", 1), ("milk", 1)]);
+        assert!(inventory.values().all(|&name| name != "test-sku"));
+    }
+}
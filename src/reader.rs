@@ -0,0 +1,259 @@
+use std::collections::HashSet;
+#[cfg(feature = "serde")]
+use std::fs::File;
+use std::io::{self, BufRead};
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Items on a line are separated by this character unless another is given.
+pub const DELIMITER: char = ',';
+
+/// Parse one line of a basket file into a transaction.
+/// Items are trimmed, quoted or not, and empty fields are dropped.
+///
+/// Fields may be quoted as in CSV, for items containing the delimiter or
+/// quotes: `"Widget, Large"` is one item, and `""` inside quotes stands for
+/// one `"`. A quote only opens a field right after the delimiter. Items
+/// cannot span lines.
+pub fn parse_transaction(line: &str) -> HashSet<String> {
+    parse_transaction_with_delimiter(line, DELIMITER)
+}
+
+/// Same as [`parse_transaction`] with items separated by `delimiter`, e.g.
+/// `'\t'` or `';'`.
+///
+/// # Panics
+///
+/// If `delimiter` is not an ASCII character.
+pub fn parse_transaction_with_delimiter(line: &str, delimiter: char) -> HashSet<String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(csv_delimiter(delimiter))
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .buffer_capacity(line.len() + 1)
+        .from_reader(line.as_bytes());
+
+    let mut record = csv::StringRecord::new();
+    let mut items = HashSet::new();
+    // a line holds at most one record; a stray line break inside quotes
+    // cannot occur, as `line` comes from splitting on them
+    if reader.read_record(&mut record).unwrap_or(false) {
+        items.extend(
+            record
+                .iter()
+                .filter(|item| !item.is_empty())
+                .map(String::from),
+        );
+    }
+
+    items
+}
+
+/// `delimiter` as the byte the `csv` reader and writer take.
+pub(crate) fn csv_delimiter(delimiter: char) -> u8 {
+    assert!(
+        delimiter.is_ascii(),
+        "delimiter {:?} is not an ASCII character",
+        delimiter
+    );
+    delimiter as u8
+}
+
+/// Read a JSON lines file where each line is an array of item names, e.g.
+/// `["bread", "milk"]`, into one transaction per line. Repeated items on a
+/// line count once, and blank lines are skipped.
+///
+/// A line that is not an array of strings fails the whole read with an
+/// [`io::ErrorKind::InvalidData`] error naming its 1-based line number.
+#[cfg(feature = "serde")]
+pub fn read_transactions_jsonl<P: AsRef<Path>>(path: P) -> io::Result<Vec<HashSet<String>>> {
+    parse_transactions_jsonl(io::BufReader::new(File::open(path)?))
+}
+
+#[cfg(feature = "serde")]
+fn parse_transactions_jsonl<R: BufRead>(reader: R) -> io::Result<Vec<HashSet<String>>> {
+    let mut transactions = vec![];
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let items: Vec<String> = serde_json::from_str(&line).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", i + 1, err),
+            )
+        })?;
+        transactions.push(items.into_iter().collect());
+    }
+
+    Ok(transactions)
+}
+
+/// Draw a uniform random sample of `k` transactions from a basket file,
+/// one transaction per line, in a single pass (reservoir sampling).
+///
+/// At most `k` transactions are held in memory regardless of the file size.
+/// Blank lines are skipped. The same `seed` always gives the same sample.
+/// If the file has fewer than `k` transactions, all of them are returned.
+pub fn sample_transactions_reservoir<R: BufRead>(
+    reader: R,
+    k: usize,
+    seed: u64,
+) -> io::Result<Vec<HashSet<String>>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut reservoir = Vec::with_capacity(k);
+    let mut seen = 0;
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if seen < k {
+            reservoir.push(parse_transaction(&line));
+        } else {
+            let j = rng.gen_range(0..=seen);
+            if j < k {
+                reservoir[j] = parse_transaction(&line);
+            }
+        }
+        seen += 1;
+    }
+
+    Ok(reservoir)
+}
+
+/// The bundled benchmark dataset: 3000 synthetic grocery baskets over 40
+/// items, with a few bundles bought together, from
+/// `benchmarks/data/groceries.csv`.
+///
+/// It is compiled into the library and never changes, so timings and results
+/// quoted against it can be reproduced by anyone. Regenerate it with
+/// `benchmarks/make_groceries.py`.
+#[cfg(feature = "bench-dataset")]
+pub fn load_bench_dataset() -> Vec<HashSet<String>> {
+    include_str!("../benchmarks/data/groceries.csv")
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_transaction)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn basket_file(num_lines: usize) -> String {
+        (0..num_lines)
+            .map(|i| format!("item {},item {}\n", i, i + 1))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_transaction() {
+        let transaction = parse_transaction(" bread, milk,,bread ");
+        let expected: HashSet<String> = vec!["bread".to_string(), "milk".to_string()]
+            .into_iter()
+            .collect();
+        assert_eq!(transaction, expected);
+    }
+
+    #[test]
+    fn test_parse_transaction_quoted() {
+        let transaction = parse_transaction(r#"bread,"Widget, Large","12"" pipe","  spaced ","""#);
+        let expected: HashSet<String> = vec!["bread", "Widget, Large", "12\" pipe", "spaced"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(transaction, expected);
+    }
+
+    #[test]
+    fn test_parse_transaction_with_delimiter() {
+        let transaction = parse_transaction_with_delimiter("a, b\tc\t\"d\te\"", '\t');
+        let expected: HashSet<String> = vec!["a, b", "c", "d\te"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(transaction, expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_read_transactions_jsonl() {
+        let path = std::env::temp_dir().join("apriori_test_read_transactions_jsonl.jsonl");
+        std::fs::write(
+            &path,
+            "[\"bread\", \"milk\"]\n\n[\"milk\", \"Widget, Large\", \"milk\"]\n[]\n",
+        )
+        .unwrap();
+
+        let transactions = read_transactions_jsonl(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let expected: Vec<HashSet<String>> =
+            vec![vec!["bread", "milk"], vec!["milk", "Widget, Large"], vec![]]
+                .into_iter()
+                .map(|items| items.into_iter().map(String::from).collect())
+                .collect();
+        assert_eq!(transactions, expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_read_transactions_jsonl_malformed_line() {
+        let file = "[\"bread\"]\n[\"milk\", 3]\n";
+        let err = parse_transactions_jsonl(Cursor::new(file)).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("line 2: "), "{}", err);
+    }
+
+    #[test]
+    fn test_reservoir_sample_size() {
+        let sample = sample_transactions_reservoir(Cursor::new(basket_file(100)), 10, 42).unwrap();
+        assert_eq!(sample.len(), 10);
+    }
+
+    #[test]
+    fn test_reservoir_smaller_than_k() {
+        let sample = sample_transactions_reservoir(Cursor::new(basket_file(3)), 10, 42).unwrap();
+        assert_eq!(sample.len(), 3);
+    }
+
+    #[test]
+    fn test_reservoir_skips_blank_lines() {
+        let file = "a,b\n\n   \nc\n";
+        let sample = sample_transactions_reservoir(Cursor::new(file), 10, 0).unwrap();
+        assert_eq!(sample.len(), 2);
+    }
+
+    #[test]
+    fn test_reservoir_is_deterministic() {
+        let sample_1 = sample_transactions_reservoir(Cursor::new(basket_file(100)), 5, 7).unwrap();
+        let sample_2 = sample_transactions_reservoir(Cursor::new(basket_file(100)), 5, 7).unwrap();
+        assert_eq!(sample_1, sample_2);
+    }
+
+    #[cfg(feature = "bench-dataset")]
+    #[test]
+    fn test_load_bench_dataset() {
+        let transactions = load_bench_dataset();
+
+        assert_eq!(transactions.len(), 3000);
+        assert!(transactions.iter().all(|transaction| !transaction.is_empty()));
+        let items: HashSet<&str> = transactions.iter().flatten().map(String::as_str).collect();
+        assert_eq!(items.len(), 40);
+        assert!(items.contains("whole milk"));
+        assert!(items.iter().all(|item| !item.is_empty() && item.trim() == *item));
+        assert_eq!(load_bench_dataset(), transactions);
+    }
+}
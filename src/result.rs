@@ -0,0 +1,207 @@
+#![allow(non_snake_case)]
+
+use std::io::{self, Write};
+
+use crate::{
+    inventory::names_to_itemset,
+    itemsets::{count::generate_frequent_itemsets, support::SupportFraction},
+    rules::{rule::Rule, search::generate_rules},
+    types::{FrequentItemsets, Inventory, ItemName, ItemsetLength, RawTransaction, ReverseLookup},
+    writer::write_fimi,
+};
+
+/// Summary of one level of a [`MiningResult`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelStats {
+    /// Number of items in each itemset of this level.
+    pub size: ItemsetLength,
+    /// Number of frequent itemsets found.
+    pub num_itemsets: usize,
+    /// Highest support count in the level, 0 if it is empty.
+    pub max_support: u32,
+}
+
+/// Frequent itemsets together with everything needed to interpret them.
+#[derive(Debug, Clone)]
+pub struct MiningResult<'l> {
+    pub itemsets: FrequentItemsets,
+    pub inventory: Inventory<'l>,
+    /// Inverse of `inventory`, from item name to id.
+    pub reverse_lookup: ReverseLookup<'l>,
+    /// Number of transactions mined.
+    pub N: usize,
+    /// One entry per level, in ascending size.
+    pub levels: Vec<LevelStats>,
+}
+
+impl<'l> MiningResult<'l> {
+    /// Mine `raw_transactions` as [`generate_frequent_itemsets`] does, keeping
+    /// the result together.
+    pub fn mine(
+        raw_transactions: Vec<RawTransaction<'l>>,
+        min_support: SupportFraction,
+        k: ItemsetLength,
+    ) -> Self {
+        let N = raw_transactions.len();
+        let (itemsets, inventory) = generate_frequent_itemsets(raw_transactions, min_support, k);
+        Self::new(itemsets, inventory, N)
+    }
+
+    /// Wrap the output of any of the tuple-returning mining functions.
+    /// `N` is the number of transactions the itemsets were mined from.
+    pub fn new(itemsets: FrequentItemsets, inventory: Inventory<'l>, N: usize) -> Self {
+        let reverse_lookup = inventory.iter().map(|(&id, &name)| (name, id)).collect();
+        let mut levels: Vec<LevelStats> = itemsets
+            .iter()
+            .map(|(&size, itemset_counts)| LevelStats {
+                size,
+                num_itemsets: itemset_counts.len(),
+                max_support: itemset_counts.values().copied().max().unwrap_or(0),
+            })
+            .collect();
+        levels.sort_unstable_by_key(|level| level.size);
+
+        Self {
+            itemsets,
+            inventory,
+            reverse_lookup,
+            N,
+            levels,
+        }
+    }
+
+    /// Every rule meeting `min_confidence`, with consequents of any length.
+    pub fn rules(&self, min_confidence: f32) -> Vec<Rule> {
+        generate_rules(&min_confidence, &self.itemsets, self.N)
+    }
+
+    /// Every frequent itemset by item name, with its support count.
+    /// Ordered as [`write_fimi`] orders lines: by length, then by name, with
+    /// names sorted within each itemset.
+    pub fn named(&self) -> Vec<(Vec<ItemName<'l>>, u32)> {
+        let mut named: Vec<(Vec<ItemName<'l>>, u32)> = self
+            .itemsets
+            .values()
+            .flat_map(|itemset_counts| itemset_counts.iter())
+            .map(|(itemset, &count)| {
+                let mut names: Vec<ItemName<'l>> = itemset
+                    .iter()
+                    .map(|item_id| self.inventory[item_id])
+                    .collect();
+                names.sort_unstable();
+                (names, count)
+            })
+            .collect();
+        named.sort_unstable_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        named
+    }
+
+    /// Support count of the itemset made of `items`, in any order.
+    /// `None` if it is not frequent, or an item was never seen.
+    pub fn support_of(&self, items: &[&str]) -> Option<u32> {
+        let mut itemset = names_to_itemset(items, &self.reverse_lookup)?;
+        itemset.dedup();
+
+        self.itemsets.get(&itemset.len())?.get(&itemset).copied()
+    }
+
+    /// Write the itemsets with [`write_fimi`].
+    pub fn to_fimi<W: Write>(&self, writer: W) -> io::Result<()> {
+        write_fimi(&self.itemsets, &self.inventory, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result() -> MiningResult<'static> {
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["bread", "milk", "cheese"],
+            vec!["bread", "milk"],
+            vec!["milk", "cheese", "bread"],
+            vec!["milk", "cheese", "yoghurt"],
+            vec!["bread", "jam"],
+            vec!["milk", "bread", "yoghurt"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        MiningResult::mine(raw_transactions, SupportFraction(0.5), 3)
+    }
+
+    #[test]
+    fn test_levels() {
+        let result = result();
+
+        assert_eq!(result.N, 6);
+        assert_eq!(
+            result.levels,
+            vec![
+                LevelStats {
+                    size: 1,
+                    num_itemsets: 3,
+                    max_support: 5
+                },
+                LevelStats {
+                    size: 2,
+                    num_itemsets: 2,
+                    max_support: 4
+                },
+                LevelStats {
+                    size: 3,
+                    num_itemsets: 0,
+                    max_support: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_named() {
+        assert_eq!(
+            result().named(),
+            vec![
+                (vec!["bread"], 5),
+                (vec!["cheese"], 3),
+                (vec!["milk"], 5),
+                (vec!["bread", "milk"], 4),
+                (vec!["cheese", "milk"], 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_support_of() {
+        let result = result();
+
+        assert_eq!(result.support_of(&["milk", "bread"]), Some(4));
+        assert_eq!(result.support_of(&["cheese"]), Some(3));
+        // seen, but not frequent
+        assert_eq!(result.support_of(&["bread", "cheese"]), None);
+        assert_eq!(result.support_of(&["caviar"]), None);
+    }
+
+    #[test]
+    fn test_rules() {
+        let result = result();
+        let rules = result.rules(0.9);
+
+        // milk -> cheese has confidence 3/5, cheese -> milk 3/3
+        assert_eq!(rules.len(), 1);
+        assert_eq!(result.inventory[&rules[0].get_antecedent()[0]], "cheese");
+        assert_eq!(result.inventory[&rules[0].get_consequent()[0]], "milk");
+    }
+
+    #[test]
+    fn test_to_fimi() {
+        let mut output = Vec::new();
+        result().to_fimi(&mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "bread (5)\ncheese (3)\nmilk (5)\nbread milk (4)\ncheese milk (3)\n"
+        );
+    }
+}
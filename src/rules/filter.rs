@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use crate::{rules::rule::Rule, types::ItemId};
+
+/// Rules whose antecedent contains `item`, most confident first.
+///
+/// Answers "given that a basket holds `item`, what else is likely in it?".
+/// Rules with equal confidence keep their relative order.
+pub fn rules_with_antecedent_item(rules: &[Rule], item: ItemId) -> Vec<&Rule> {
+    let mut matching: Vec<&Rule> = rules
+        .iter()
+        .filter(|rule| rule.get_antecedent().contains(&item))
+        .collect();
+    matching.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    matching
+}
+
+/// Drop redundant rules, keeping the rest in their original order.
+///
+/// A rule `X -> Y` is redundant if some other rule `X' -> Y` with the same
+/// consequent has an antecedent `X'` that is a proper subset of `X` and a
+/// confidence at least as high: the extra items in `X` add nothing. What is
+/// left, for each consequent, are the rules whose antecedent is minimal among
+/// those reaching their confidence. Only rules present in `rules` are compared.
+pub fn remove_redundant_rules(rules: Vec<Rule>) -> Vec<Rule> {
+    let mut by_consequent: HashMap<&[ItemId], Vec<usize>> = HashMap::new();
+    for (index, rule) in rules.iter().enumerate() {
+        by_consequent
+            .entry(rule.get_consequent())
+            .or_default()
+            .push(index);
+    }
+
+    let mut redundant = vec![false; rules.len()];
+    for indices in by_consequent.values() {
+        for &i in indices {
+            let rule = &rules[i];
+            redundant[i] = indices.iter().any(|&j| {
+                let other = &rules[j];
+                other.get_antecedent().len() < rule.get_antecedent().len()
+                    && other.confidence >= rule.confidence
+                    && other
+                        .get_antecedent()
+                        .iter()
+                        .all(|item| rule.get_antecedent().contains(item))
+            });
+        }
+    }
+
+    rules
+        .into_iter()
+        .zip(redundant)
+        .filter(|(_, redundant)| !redundant)
+        .map(|(rule, _)| rule)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::support::SupportCount;
+
+    fn rule(antecedent: &[ItemId], consequent: &[ItemId], confidence: f32) -> Rule {
+        Rule {
+            split: antecedent.len(),
+            combi: antecedent.iter().chain(consequent).copied().collect(),
+            confidence,
+            lift: 1.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
+        }
+    }
+
+    #[test]
+    fn test_rules_with_antecedent_item() {
+        let rules = vec![
+            rule(&[0], &[1], 0.5),
+            rule(&[1], &[0], 0.9),
+            rule(&[0, 2], &[1], 0.8),
+            rule(&[2], &[0], 0.7),
+            rule(&[0], &[2], 0.6),
+        ];
+
+        let matching = rules_with_antecedent_item(&rules, 0);
+
+        let confidences: Vec<f32> = matching.iter().map(|rule| rule.confidence).collect();
+        assert_eq!(confidences, vec![0.8, 0.6, 0.5]);
+        assert!(matching
+            .iter()
+            .all(|rule| rule.get_antecedent().contains(&0)));
+
+        assert!(rules_with_antecedent_item(&rules, 3).is_empty());
+    }
+
+    #[test]
+    fn test_remove_redundant_rules() {
+        let rules = vec![
+            rule(&[0], &[2], 0.8),
+            // no better than {0} -> {2}
+            rule(&[0, 1], &[2], 0.8),
+            // more confident than {0} -> {2}, so it stays
+            rule(&[0, 3], &[2], 0.9),
+            // {1} alone is less confident
+            rule(&[1], &[2], 0.5),
+            // same antecedent items, different consequent
+            rule(&[0, 1], &[3], 0.4),
+            rule(&[0, 1, 3], &[2], 0.85),
+        ];
+
+        let kept: Vec<(Vec<ItemId>, Vec<ItemId>)> = remove_redundant_rules(rules)
+            .iter()
+            .map(|rule| (rule.get_antecedent().to_vec(), rule.get_consequent().to_vec()))
+            .collect();
+
+        assert_eq!(
+            kept,
+            vec![
+                (vec![0], vec![2]),
+                (vec![0, 3], vec![2]),
+                (vec![1], vec![2]),
+                (vec![0, 1], vec![3]),
+            ]
+        );
+    }
+}
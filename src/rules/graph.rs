@@ -0,0 +1,111 @@
+use crate::{rules::rule::Rule, types::ItemId};
+
+/// How [`rules_to_edgelist`] turns a rule with several items on either side
+/// into edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeExpansion {
+    /// One edge from every antecedent item to every consequent item.
+    Pairwise,
+    /// One synthetic node per rule, with edges from each antecedent item into
+    /// it and from it to each consequent item. Rule nodes are numbered after
+    /// the largest item id, in the order the rules are given, so they never
+    /// collide with items. This keeps `{a, b} -> {c}` distinguishable from
+    /// `{a} -> {c}` plus `{b} -> {c}`.
+    RuleNode,
+}
+
+/// Express `rules` as a directed graph edge list of
+/// `(from node, to node, confidence)`, e.g. for export to a graph tool.
+///
+/// Item nodes are identified by their item id. Every edge carries the
+/// confidence of the rule it came from; several rules can yield the same
+/// pair, in which case the edge appears once per rule.
+pub fn rules_to_edgelist(rules: &[Rule], expansion: EdgeExpansion) -> Vec<(usize, usize, f32)> {
+    match expansion {
+        EdgeExpansion::Pairwise => rules
+            .iter()
+            .flat_map(|rule| {
+                rule.get_antecedent().iter().flat_map(move |&from| {
+                    rule.get_consequent()
+                        .iter()
+                        .map(move |&to| (from, to, rule.confidence))
+                })
+            })
+            .collect(),
+        EdgeExpansion::RuleNode => {
+            let first_rule_node = rules
+                .iter()
+                .flat_map(|rule| rule.combi.iter().copied())
+                .max()
+                .map_or(0, |max_item: ItemId| max_item + 1);
+
+            rules
+                .iter()
+                .enumerate()
+                .flat_map(|(index, rule)| {
+                    let rule_node = first_rule_node + index;
+                    let incoming = rule
+                        .get_antecedent()
+                        .iter()
+                        .map(move |&from| (from, rule_node, rule.confidence));
+                    let outgoing = rule
+                        .get_consequent()
+                        .iter()
+                        .map(move |&to| (rule_node, to, rule.confidence));
+                    incoming.chain(outgoing)
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::support::SupportCount;
+
+    fn rule(antecedent: &[ItemId], consequent: &[ItemId], confidence: f32) -> Rule {
+        Rule {
+            split: antecedent.len(),
+            combi: antecedent.iter().chain(consequent).copied().collect(),
+            confidence,
+            lift: 1.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
+        }
+    }
+
+    fn rules() -> Vec<Rule> {
+        vec![rule(&[0, 1], &[2], 0.75), rule(&[2], &[0, 3], 0.5)]
+    }
+
+    #[test]
+    fn test_pairwise_edges() {
+        assert_eq!(
+            rules_to_edgelist(&rules(), EdgeExpansion::Pairwise),
+            vec![(0, 2, 0.75), (1, 2, 0.75), (2, 0, 0.5), (2, 3, 0.5)]
+        );
+    }
+
+    #[test]
+    fn test_rule_node_edges() {
+        // items go up to 3, so the rules become nodes 4 and 5
+        assert_eq!(
+            rules_to_edgelist(&rules(), EdgeExpansion::RuleNode),
+            vec![
+                (0, 4, 0.75),
+                (1, 4, 0.75),
+                (4, 2, 0.75),
+                (2, 5, 0.5),
+                (5, 0, 0.5),
+                (5, 3, 0.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_rules() {
+        assert!(rules_to_edgelist(&[], EdgeExpansion::Pairwise).is_empty());
+        assert!(rules_to_edgelist(&[], EdgeExpansion::RuleNode).is_empty());
+    }
+}
@@ -1,2 +1,7 @@
+pub mod filter;
+pub mod graph;
+pub mod rank;
 pub mod search;
-pub mod rule;
\ No newline at end of file
+pub mod rule;
+pub mod summary;
+pub mod validate;
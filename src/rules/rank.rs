@@ -0,0 +1,358 @@
+#![allow(non_snake_case)]
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::{
+    itemsets::{count::generate_frequent_itemsets, support::SupportFraction},
+    rules::rule::{NamedRule, Rule},
+    rules::search::{bfs, generate_rules, generate_rules_limited},
+    types::{FrequentItemsets, ItemId, RawTransaction},
+};
+
+/// Orders rules by lift alone, for use in a heap.
+struct ByLift(Rule);
+
+impl PartialEq for ByLift {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ByLift {}
+
+impl PartialOrd for ByLift {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByLift {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.lift.total_cmp(&other.0.lift)
+    }
+}
+
+/// The `n` rules with the highest lift, highest first.
+///
+/// Rules are enumerated one frequent itemset at a time and kept in a min-heap
+/// of size `n`, so memory stays O(n) rather than holding every rule.
+/// `N` is the number of transactions the itemsets were mined from.
+pub fn top_rules_by_lift(itemsets: &FrequentItemsets, n: usize, N: usize) -> Vec<Rule> {
+    if n == 0 {
+        return vec![];
+    }
+
+    let mut heap: BinaryHeap<Reverse<ByLift>> = BinaryHeap::with_capacity(n + 1);
+
+    for (_, itemset_counts) in itemsets.iter().filter(|(&size, _)| size > 1) {
+        for (combi, &count) in itemset_counts {
+            for rule in bfs(combi, count, &0.0, itemsets, N as f32, combi.len()) {
+                heap.push(Reverse(ByLift(rule)));
+                if heap.len() > n {
+                    heap.pop();
+                }
+            }
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(ByLift(rule))| rule)
+        .collect()
+}
+
+/// Mine, generate rules and keep the `top_n` with the highest lift, by name:
+/// the interesting associations in one call.
+///
+/// Itemsets are mined up to the length of the longest transaction, so no
+/// pattern is cut short, and every rule meeting `min_confidence` is ranked.
+/// Ties in lift go to the more confident rule, then to the rule with the
+/// smaller names, so the result does not depend on hashing.
+pub fn top_associations<'l>(
+    raw_transactions: Vec<RawTransaction<'l>>,
+    min_support: SupportFraction,
+    min_confidence: f32,
+    top_n: usize,
+) -> Vec<NamedRule<'l>> {
+    let N = raw_transactions.len();
+    let k = raw_transactions
+        .iter()
+        .map(|transaction| transaction.len())
+        .max()
+        .unwrap_or(0);
+    let (itemsets, inventory) = generate_frequent_itemsets(raw_transactions, min_support, k);
+
+    let mut rules: Vec<NamedRule> = generate_rules(&min_confidence, &itemsets, N)
+        .iter()
+        .map(|rule| rule.named(&inventory))
+        .collect();
+    rules.sort_by(|a, b| {
+        b.lift
+            .total_cmp(&a.lift)
+            .then_with(|| b.confidence.total_cmp(&a.confidence))
+            .then_with(|| a.antecedent.cmp(&b.antecedent))
+            .then_with(|| a.consequent.cmp(&b.consequent))
+    });
+    rules.truncate(top_n);
+    rules
+}
+
+/// Every rule meeting `min_confidence` whose consequent is a bundle of 2 to
+/// `max_consequent_len` items, with its improvement, highest first.
+///
+/// The improvement of `A -> B` is its gain over the best rule `A -> B'` with
+/// a smaller consequent, over every proper non-empty subset `B'` of `B`:
+///
+/// ```text
+/// improvement = lift(A -> B) - max lift(A -> B')
+/// ```
+///
+/// The gain is taken in lift rather than confidence because, with `A` fixed,
+/// confidence can only drop as the consequent grows, so a confidence gain is
+/// never positive and cannot single out a bundle. Lift measures each rule
+/// against the support of its own consequent: a positive improvement means
+/// `A` predicts the whole bundle better than it predicts any part of it,
+/// single items and smaller bundles alike, and 0 or less means some part
+/// carries the rule. Ties go to the more confident rule, then to the smaller
+/// antecedent and consequent.
+pub fn bundle_rules_by_improvement(
+    min_confidence: f32,
+    itemsets: &FrequentItemsets,
+    N: usize,
+    max_consequent_len: usize,
+) -> Vec<(Rule, f32)> {
+    let support = |itemset: &[ItemId]| {
+        let mut itemset = itemset.to_vec();
+        itemset.sort_unstable();
+        itemsets[&itemset.len()][itemset.as_slice()] as f32
+    };
+    // every subset of a frequent itemset is frequent, so every part's
+    // supports are in `itemsets`
+    let best_part_lift = |rule: &Rule| {
+        let antecedent = rule.get_antecedent();
+        let consequent = rule.get_consequent();
+        (1..(1_usize << consequent.len()) - 1)
+            .map(|mask| {
+                let part: Vec<ItemId> = consequent
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| mask & (1 << i) != 0)
+                    .map(|(_, &item)| item)
+                    .collect();
+                let union: Vec<ItemId> = antecedent.iter().chain(&part).copied().collect();
+                support(&union) * N as f32 / (rule.antecedent_support.0 as f32 * support(&part))
+            })
+            .fold(f32::NEG_INFINITY, f32::max)
+    };
+
+    let mut rules: Vec<(Rule, f32)> =
+        generate_rules_limited(&min_confidence, itemsets, N, max_consequent_len)
+            .into_iter()
+            .filter(|rule| rule.get_consequent().len() > 1)
+            .map(|rule| {
+                let improvement = rule.lift - best_part_lift(&rule);
+                (rule, improvement)
+            })
+            .collect();
+    rules.sort_by(|(a, a_improvement), (b, b_improvement)| {
+        b_improvement
+            .total_cmp(a_improvement)
+            .then_with(|| b.confidence.total_cmp(&a.confidence))
+            .then_with(|| a.get_antecedent().cmp(b.get_antecedent()))
+            .then_with(|| a.get_consequent().cmp(b.get_consequent()))
+    });
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> FrequentItemsets {
+        hashmap! {
+            1 => hashmap! {
+                itemset![1] => 9,
+                itemset![2] => 8,
+                itemset![3] => 12,
+                itemset![4] => 13,
+            },
+            2 => hashmap! {
+                itemset![1, 2] => 4,
+                itemset![1, 3] => 5,
+                itemset![1, 4] => 6,
+                itemset![2, 3] => 3,
+                itemset![2, 4] => 5,
+                itemset![3, 4] => 3,
+            },
+            3 => hashmap! {
+                itemset![1, 2, 3] => 3,
+                itemset![1, 2, 4] => 3,
+                itemset![1, 3, 4] => 3,
+                itemset![2, 3, 4] => 3,
+            },
+            4 => hashmap! {
+                itemset![1, 2, 3, 4] => 2,
+            },
+        }
+    }
+
+    #[test]
+    fn test_top_rules_match_full_sort() {
+        let itemsets = fixture();
+        let N = 20;
+
+        let mut all_rules = generate_rules(&0.0, &itemsets, N);
+        all_rules.sort_by(|a, b| b.lift.total_cmp(&a.lift));
+        let expected: Vec<f32> = all_rules.iter().take(5).map(|rule| rule.lift).collect();
+
+        let top_rules = top_rules_by_lift(&itemsets, 5, N);
+        let lifts: Vec<f32> = top_rules.iter().map(|rule| rule.lift).collect();
+
+        assert_eq!(lifts, expected);
+    }
+
+    #[test]
+    fn test_top_rules_more_than_available() {
+        let itemsets = fixture();
+        let all_rules = generate_rules(&0.0, &itemsets, 20);
+
+        assert_eq!(
+            top_rules_by_lift(&itemsets, 1000, 20).len(),
+            all_rules.len()
+        );
+        assert!(top_rules_by_lift(&itemsets, 0, 20).is_empty());
+    }
+
+    #[test]
+    fn test_top_associations() {
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["bread", "butter", "milk"],
+            vec!["bread", "butter"],
+            vec!["bread", "butter", "jam"],
+            vec!["milk", "cereal"],
+            vec!["milk", "cereal", "bread"],
+            vec!["milk", "cereal"],
+            vec!["jam", "tea"],
+            vec!["bread"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        let rules = top_associations(raw_transactions.clone(), SupportFraction(0.25), 0.5, 3);
+
+        assert_eq!(rules.len(), 3);
+        assert!(rules.windows(2).all(|pair| pair[0].lift >= pair[1].lift));
+        assert!(rules.iter().all(|rule| rule.confidence >= 0.5));
+        // cereal only ever comes with milk, and half of all baskets have milk
+        let names: Vec<(&[&str], &[&str])> = rules
+            .iter()
+            .map(|rule| (rule.antecedent.as_slice(), rule.consequent.as_slice()))
+            .collect();
+        assert_eq!(
+            names[..2],
+            [
+                (&["cereal"][..], &["milk"][..]),
+                (&["milk"][..], &["cereal"][..])
+            ]
+        );
+        assert!((rules[0].lift - 2.0).abs() < 1e-6);
+        assert_eq!(rules[0].union_support.0, 3);
+
+        assert!(top_associations(raw_transactions, SupportFraction(0.25), 0.5, 0).is_empty());
+    }
+
+    #[test]
+    fn test_bundle_rules_by_improvement() {
+        let itemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 5,
+                itemset![1] => 6,
+                itemset![2] => 6,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 4,
+                itemset![0, 2] => 5,
+                itemset![1, 2] => 4,
+            },
+            3 => hashmap! {
+                itemset![0, 1, 2] => 4,
+            },
+        };
+
+        let rules = bundle_rules_by_improvement(0.0, &itemsets, 10, 2);
+        let scores: Vec<(&[ItemId], &[ItemId], f32)> = rules
+            .iter()
+            .map(|(rule, improvement)| (rule.get_antecedent(), rule.get_consequent(), *improvement))
+            .collect();
+
+        assert_eq!(scores.len(), 3);
+        // 0.8 / 0.4 - max(0.8 / 0.6, 1.0 / 0.6): buying 0 lifts 1 and 2
+        // together more than it lifts either alone
+        assert_eq!(scores[0].0, [0]);
+        assert_eq!(scores[0].1, [1, 2]);
+        assert!((scores[0].2 - 1.0 / 3.0).abs() < 1e-6);
+        // 1 -> {0, 2}: 4/6 / 0.5 - max(4/6 / 0.5, 4/6 / 0.6)
+        // 2 -> {0, 1}: 4/6 / 0.4 - max(5/6 / 0.5, 4/6 / 0.6)
+        // both bundles are no more than their best part
+        let mut rest: Vec<&[ItemId]> = scores[1..].iter().map(|score| score.0).collect();
+        rest.sort_unstable();
+        assert_eq!(rest, [[1], [2]]);
+        assert!(scores[1..].iter().all(|score| score.2.abs() < 1e-6));
+
+        assert!(bundle_rules_by_improvement(0.0, &itemsets, 10, 1).is_empty());
+    }
+
+    #[test]
+    fn test_bundle_improvement_over_sub_bundles() {
+        // of 10 transactions, 0 comes with the pair {1, 2} whenever it comes
+        // with either, so 0 -> {1, 2} carries 0 -> {1, 2, 3}
+        let itemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 5,
+                itemset![1] => 5,
+                itemset![2] => 5,
+                itemset![3] => 5,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 3,
+                itemset![0, 2] => 3,
+                itemset![0, 3] => 3,
+                itemset![1, 2] => 3,
+                itemset![1, 3] => 3,
+                itemset![2, 3] => 3,
+            },
+            3 => hashmap! {
+                itemset![0, 1, 2] => 3,
+                itemset![0, 1, 3] => 2,
+                itemset![0, 2, 3] => 2,
+                itemset![1, 2, 3] => 2,
+            },
+            4 => hashmap! {
+                itemset![0, 1, 2, 3] => 2,
+            },
+        };
+
+        let rules = bundle_rules_by_improvement(0.0, &itemsets, 10, 3);
+        let improvement = |consequent: &[ItemId]| {
+            rules
+                .iter()
+                .find(|(rule, _)| {
+                    let mut rule_consequent = rule.get_consequent().to_vec();
+                    rule_consequent.sort_unstable();
+                    rule.get_antecedent() == [0] && rule_consequent == consequent
+                })
+                .map(|&(_, improvement)| improvement)
+                .unwrap()
+        };
+
+        // lift 3 * 10 / (5 * 3) = 2 over the singles' 3 * 10 / (5 * 5) = 1.2
+        assert!((improvement(&[1, 2]) - 0.8).abs() < 1e-5);
+        // lift 2 * 10 / (5 * 2) = 2, no better than its part {1, 2}, though
+        // 0.8 better than any single item
+        assert!(improvement(&[1, 2, 3]).abs() < 1e-5);
+        // lift 2 * 10 / (5 * 3) = 4/3 against 1.2 for 1 or 3 alone
+        assert!((improvement(&[1, 3]) - (4.0 / 3.0 - 1.2)).abs() < 1e-5);
+    }
+}
@@ -1,6 +1,9 @@
 #![allow(non_snake_case)]
 
-use crate::types::{FrequentItemsets, ItemId};
+use crate::{
+    itemsets::support::SupportCount,
+    types::{FrequentItemsets, Inventory, ItemId, ItemName},
+};
 use std::collections::VecDeque;
 
 #[derive(Debug)]
@@ -9,15 +12,21 @@ pub struct Rule {
     pub combi: Vec<ItemId>,
     pub confidence: f32,
     pub lift: f32,
+    /// Number of transactions containing the antecedent.
+    pub antecedent_support: SupportCount,
+    /// Number of transactions containing both antecedent and consequent.
+    pub union_support: SupportCount,
 }
 
 impl Rule {
     pub fn from_pattern(pattern: &[ItemId]) -> Vec<Rule> {
         let mother = Rule {
             split: pattern.len(),
-            combi: pattern.iter().copied().collect(),
+            combi: pattern.to_vec(),
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         mother.create_children(&[], None).unwrap()
     }
@@ -49,6 +58,8 @@ impl Rule {
                 combi,
                 confidence: 0.0,
                 lift: 0.0,
+                antecedent_support: SupportCount::default(),
+                union_support: SupportCount::default(),
             };
 
             if rule.is_going_to_be_created(to_create) {
@@ -95,17 +106,56 @@ impl Rule {
         let conseq = self.get_consequent();
         parent.get_consequent().iter().all(|x| conseq.contains(x))
     }
-    pub fn compute_confidence(&mut self, counter: &FrequentItemsets, combi: &[ItemId], N: f32) {
-        let antecedent_support_count =
-            counter[&self.get_antecedent().len()][self.get_antecedent()] as f32;
+    pub fn compute_confidence(
+        &mut self,
+        counter: &FrequentItemsets,
+        union_support_count: u32,
+        N: f32,
+    ) {
+        self.antecedent_support =
+            SupportCount(counter[&self.get_antecedent().len()][self.get_antecedent()]);
+        self.union_support = SupportCount(union_support_count);
+        let antecedent_support_count = self.antecedent_support.0 as f32;
         let consequent_support_count =
             counter[&self.get_consequent().len()][self.get_consequent()] as f32;
-        let union_support_count = counter[&self.combi.len()][combi] as f32;
+        let union_support_count = union_support_count as f32;
         self.confidence = union_support_count / antecedent_support_count;
         self.lift = union_support_count / (antecedent_support_count * consequent_support_count) * N
     }
 }
 
+/// A [`Rule`] with its items resolved to names, each side sorted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedRule<'l> {
+    pub antecedent: Vec<ItemName<'l>>,
+    pub consequent: Vec<ItemName<'l>>,
+    pub confidence: f32,
+    pub lift: f32,
+    pub antecedent_support: SupportCount,
+    pub union_support: SupportCount,
+}
+
+impl Rule {
+    /// Resolve the items of this rule through `inventory`.
+    pub fn named<'l>(&self, inventory: &Inventory<'l>) -> NamedRule<'l> {
+        let names = |items: &[ItemId]| {
+            let mut names: Vec<ItemName<'l>> =
+                items.iter().map(|item_id| inventory[item_id]).collect();
+            names.sort_unstable();
+            names
+        };
+
+        NamedRule {
+            antecedent: names(self.get_antecedent()),
+            consequent: names(self.get_consequent()),
+            confidence: self.confidence,
+            lift: self.lift,
+            antecedent_support: self.antecedent_support,
+            union_support: self.union_support,
+        }
+    }
+}
+
 impl PartialEq for Rule {
     fn eq(&self, other: &Rule) -> bool {
         // assumes same pattern
@@ -137,12 +187,16 @@ mod test {
             combi: vec![1, 2, 3, 5],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         let rule2 = Rule {
             split: 2,
             combi: vec![1, 2, 3, 5],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         assert!(rule1 == rule2);
     }
@@ -153,12 +207,16 @@ mod test {
             combi: vec![1, 2, 3, 5],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         let rule2 = Rule {
             split: 2,
             combi: vec![9, 10, 3, 5],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         assert!(rule1 == rule2);
     }
@@ -169,12 +227,16 @@ mod test {
             combi: vec![1, 2, 3, 5],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         let rule2 = Rule {
             split: 2,
             combi: vec![9, 10, 5],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         assert!(rule1 != rule2);
     }
@@ -185,12 +247,16 @@ mod test {
             combi: vec![1, 3, 4, 2],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         }]);
         let rule = Rule {
             split: 2,
             combi: vec![3, 5, 1, 2],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         assert!(!rules.contains(&rule));
     }
@@ -202,6 +268,8 @@ mod test {
             combi: vec![1, 2, 3, 4, 5],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         let mut children = rule.create_children(&[], None).unwrap();
         let child = children.pop().unwrap();
@@ -228,6 +296,8 @@ mod test {
             ],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         let child = Rule {
             split: 3,
@@ -237,6 +307,8 @@ mod test {
             ],
             confidence: 0.0,
             lift: 0.0,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
         };
         assert!(child.is_child_of(&parent));
     }
@@ -3,12 +3,24 @@
 use std::collections::VecDeque;
 
 use crate::{
+    itemsets::condensed::filter_closed,
     rules::rule::Rule,
-    types::{FrequentItemsets, ItemId, Itemset},
+    types::{FrequentItemsets, ItemId},
 };
 
 /// Generate rules based on frequent itemsets
 pub fn generate_rules(min_conf: &f32, counter: &FrequentItemsets, N: usize) -> Vec<Rule> {
+    generate_rules_limited(min_conf, counter, N, usize::MAX)
+}
+
+/// Same as [`generate_rules`], but only rules with at most
+/// `max_consequent_len` items on the right-hand side are generated.
+pub fn generate_rules_limited(
+    min_conf: &f32,
+    counter: &FrequentItemsets,
+    N: usize,
+    max_consequent_len: usize,
+) -> Vec<Rule> {
     let N = N as f32;
     counter
         .iter()
@@ -22,21 +34,82 @@ pub fn generate_rules(min_conf: &f32, counter: &FrequentItemsets, N: usize) -> V
         .flat_map(|itemset_counts| {
             itemset_counts
                 .iter()
-                .flat_map(|(combi, _)| {
-                    let combi: Itemset = combi.iter().copied().collect();
-                    bfs(&combi, min_conf, counter, N)
+                .flat_map(|(combi, &count)| {
+                    bfs(combi, count, min_conf, counter, N, max_consequent_len)
                 })
                 .collect::<Vec<Rule>>()
         })
         .collect()
 }
 
-/// Given a combination, find a list of rules that can be generated from it
-pub fn bfs(combi: &[ItemId], &min_conf: &f32, counter: &FrequentItemsets, N: f32) -> Vec<Rule> {
+/// Same as [`generate_rules`], but only from the closed itemsets (see
+/// [`filter_closed`]).
+///
+/// A rule from a non-closed itemset `X` has the same support and confidence
+/// as the rule moving the extra items of `X`'s closure into the consequent,
+/// so this keeps a much smaller, non-redundant set of rules from which the
+/// rest can be told apart. Confidence and lift are still computed from all of
+/// `counter`.
+pub fn generate_rules_from_closed(
+    min_conf: &f32,
+    counter: &FrequentItemsets,
+    N: usize,
+) -> Vec<Rule> {
+    generate_rules_from_closed_limited(min_conf, counter, N, usize::MAX)
+}
+
+/// Same as [`generate_rules_from_closed`], but only rules with at most
+/// `max_consequent_len` items on the right-hand side are generated.
+pub fn generate_rules_from_closed_limited(
+    min_conf: &f32,
+    counter: &FrequentItemsets,
+    N: usize,
+    max_consequent_len: usize,
+) -> Vec<Rule> {
+    let N = N as f32;
+    filter_closed(counter)
+        .into_iter()
+        .filter(|(combi, _)| combi.len() > 1)
+        .flat_map(|(combi, count)| bfs(&combi, count, min_conf, counter, N, max_consequent_len))
+        .collect()
+}
+
+/// All rules that can be generated from a single frequent itemset with
+/// support count `count`, i.e. every antecedent/consequent split meeting `min_confidence`.
+pub fn rules_from_itemset(
+    itemset: &[ItemId],
+    count: u32,
+    itemsets: &FrequentItemsets,
+    min_confidence: f32,
+    N: usize,
+) -> Vec<Rule> {
+    bfs(
+        itemset,
+        count,
+        &min_confidence,
+        itemsets,
+        N as f32,
+        itemset.len(),
+    )
+}
+
+/// Given a combination and its support count, find a list of rules that can be generated from it
+pub fn bfs(
+    combi: &[ItemId],
+    union_support_count: u32,
+    &min_conf: &f32,
+    counter: &FrequentItemsets,
+    N: f32,
+    max_consequent_len: usize,
+) -> Vec<Rule> {
     let mut queue: VecDeque<Rule> = VecDeque::new();
     let mut blacklist = vec![];
     let mut final_rules = vec![];
 
+    if max_consequent_len == 0 {
+        return final_rules;
+    }
+
     let rules = Rule::from_pattern(combi);
     queue.extend(rules);
 
@@ -45,11 +118,14 @@ pub fn bfs(combi: &[ItemId], &min_conf: &f32, counter: &FrequentItemsets, N: f32
             continue;
         }
 
-        rule.compute_confidence(counter, combi, N);
+        rule.compute_confidence(counter, union_support_count, N);
 
         if rule.confidence >= min_conf {
-            if let Some(new_rules) = rule.create_children(&blacklist, Some(&queue)) {
-                queue.extend(new_rules);
+            // children move one more item into the consequent
+            if rule.get_consequent().len() < max_consequent_len {
+                if let Some(new_rules) = rule.create_children(&blacklist, Some(&queue)) {
+                    queue.extend(new_rules);
+                }
             }
             final_rules.push(rule);
         } else {
@@ -63,79 +139,286 @@ pub fn bfs(combi: &[ItemId], &min_conf: &f32, counter: &FrequentItemsets, N: f32
 #[cfg(test)]
 mod tests {
     use super::*;
-    use maplit::hashmap;
 
-    use crate::types::FrequentItemsets;
+    use crate::{itemsets::support::SupportCount, types::FrequentItemsets};
+
+    /// Antecedent and consequent of each rule, both sorted, in sorted order.
+    fn splits(rules: &[Rule]) -> Vec<(Vec<ItemId>, Vec<ItemId>)> {
+        let mut splits: Vec<(Vec<ItemId>, Vec<ItemId>)> = rules
+            .iter()
+            .map(|rule| {
+                let mut antecedent = rule.get_antecedent().to_vec();
+                let mut consequent = rule.get_consequent().to_vec();
+                antecedent.sort_unstable();
+                consequent.sort_unstable();
+                (antecedent, consequent)
+            })
+            .collect();
+        splits.sort_unstable();
+        splits
+    }
 
     #[test]
     fn test_1() {
         let counter: FrequentItemsets = hashmap! {
             1 => hashmap! {
-                vec![1] => 9,
-                vec![2] => 8,
-                vec![3] => 12,
-                vec![4] => 13,
+                itemset![1] => 9,
+                itemset![2] => 8,
+                itemset![3] => 12,
+                itemset![4] => 13,
             },
             2 => hashmap! {
-                vec![1, 2] => 4,
-                vec![1, 3] => 5,
-                vec![1, 4] => 6,
-                vec![2, 3] => 3,
-                vec![2, 4] => 5,
-                vec![3, 4] => 3,
+                itemset![1, 2] => 4,
+                itemset![1, 3] => 5,
+                itemset![1, 4] => 6,
+                itemset![2, 3] => 3,
+                itemset![2, 4] => 5,
+                itemset![3, 4] => 3,
             },
             3 => hashmap! {
-                vec![1, 2, 3] => 3,
-                vec![1, 2, 4] => 3,
-                vec![1, 3, 4] => 3,
-                vec![2, 3, 4] => 3,
+                itemset![1, 2, 3] => 3,
+                itemset![1, 2, 4] => 3,
+                itemset![1, 3, 4] => 3,
+                itemset![2, 3, 4] => 3,
             },
             4 => hashmap! {
-                vec![1, 2, 3, 4] => 2,
+                itemset![1, 2, 3, 4] => 2,
             },
         };
         let min_conf = 0.8;
 
         let assoc_rules = generate_rules(&min_conf, &counter, 1);
 
-        for r in &assoc_rules {
-            println!("{}", r);
-        }
+        // only the 2-item antecedents always seen with a third item
+        assert_eq!(
+            splits(&assoc_rules),
+            vec![
+                (vec![2, 3], vec![1]),
+                (vec![2, 3], vec![4]),
+                (vec![3, 4], vec![1]),
+                (vec![3, 4], vec![2]),
+            ]
+        );
+        assert!(assoc_rules
+            .iter()
+            .all(|rule| (rule.confidence - 1.0).abs() < 1e-6));
     }
 
     #[test]
     fn test_2() {
         let counter: FrequentItemsets = hashmap! {
             1 => hashmap! {
-                vec![1] => 9,
-                vec![2] => 8,
-                vec![3] => 12,
-                vec![4] => 13,
+                itemset![1] => 9,
+                itemset![2] => 8,
+                itemset![3] => 12,
+                itemset![4] => 13,
             },
             2 => hashmap! {
-                vec![1, 2] => 4,
-                vec![1, 3] => 5,
-                vec![1, 4] => 6,
-                vec![2, 3] => 3,
-                vec![2, 4] => 5,
-                vec![3, 4] => 3,
+                itemset![1, 2] => 4,
+                itemset![1, 3] => 5,
+                itemset![1, 4] => 6,
+                itemset![2, 3] => 3,
+                itemset![2, 4] => 5,
+                itemset![3, 4] => 3,
             },
             3 => hashmap! {
-                vec![1, 2, 3] => 3,
-                vec![1, 2, 4] => 3,
-                vec![1, 3, 4] => 3,
-                vec![2, 3, 4] => 3,
+                itemset![1, 2, 3] => 3,
+                itemset![1, 2, 4] => 3,
+                itemset![1, 3, 4] => 3,
+                itemset![2, 3, 4] => 3,
             },
             4 => hashmap! {
-                vec![1, 2, 3, 4] => 2,
+                itemset![1, 2, 3, 4] => 2,
             },
         };
         let min_conf = 0.8;
 
         let assoc_rules = generate_rules(&min_conf, &counter, 1);
 
-        for r in &assoc_rules {
-            println!("{}", r);
+        assert_eq!(
+            splits(&assoc_rules),
+            vec![
+                (vec![2, 3], vec![1]),
+                (vec![2, 3], vec![4]),
+                (vec![3, 4], vec![1]),
+                (vec![3, 4], vec![2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_consequent_len() {
+        let counter: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![1] => 3,
+                itemset![2] => 3,
+                itemset![3] => 3,
+            },
+            2 => hashmap! {
+                itemset![1, 2] => 3,
+                itemset![1, 3] => 3,
+                itemset![2, 3] => 3,
+            },
+            3 => hashmap! {
+                itemset![1, 2, 3] => 3,
+            },
+        };
+
+        let all_rules = generate_rules(&0.0, &counter, 3);
+        assert!(all_rules.iter().any(|rule| rule.get_consequent().len() == 2));
+        assert_eq!(
+            generate_rules_limited(&0.0, &counter, 3, 3).len(),
+            all_rules.len()
+        );
+
+        let rules = generate_rules_limited(&0.0, &counter, 3, 1);
+        assert!(!rules.is_empty());
+        assert!(rules.iter().all(|rule| rule.get_consequent().len() == 1));
+        // 2 rules from each 2-itemset, 3 from the 3-itemset
+        assert_eq!(rules.len(), 9);
+
+        assert!(generate_rules_limited(&0.0, &counter, 3, 0).is_empty());
+
+        let counter: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![1] => 9,
+                itemset![2] => 8,
+                itemset![3] => 12,
+                itemset![4] => 13,
+            },
+            2 => hashmap! {
+                itemset![1, 2] => 4,
+                itemset![1, 3] => 5,
+                itemset![1, 4] => 6,
+                itemset![2, 3] => 3,
+                itemset![2, 4] => 5,
+                itemset![3, 4] => 3,
+            },
+            3 => hashmap! {
+                itemset![1, 2, 3] => 3,
+                itemset![1, 2, 4] => 3,
+                itemset![1, 3, 4] => 3,
+                itemset![2, 3, 4] => 3,
+            },
+            4 => hashmap! {
+                itemset![1, 2, 3, 4] => 2,
+            },
+        };
+        let min_conf = 0.65;
+
+        let single_item_consequents = vec![
+            (vec![1], vec![4]),
+            (vec![1, 2], vec![3]),
+            (vec![1, 2], vec![4]),
+            (vec![1, 2, 3], vec![4]),
+            (vec![1, 2, 4], vec![3]),
+            (vec![1, 3, 4], vec![2]),
+            (vec![2, 3], vec![1]),
+            (vec![2, 3], vec![4]),
+            (vec![2, 3, 4], vec![1]),
+            (vec![3, 4], vec![1]),
+            (vec![3, 4], vec![2]),
+        ];
+        assert_eq!(
+            splits(&generate_rules_limited(&min_conf, &counter, 1, 1)),
+            single_item_consequents
+        );
+
+        // {2, 3} and {3, 4} are each seen 3 times, twice with the other two items
+        let mut expected = single_item_consequents;
+        expected.push((vec![2, 3], vec![1, 4]));
+        expected.push((vec![3, 4], vec![1, 2]));
+        expected.sort_unstable();
+        assert_eq!(splits(&generate_rules(&min_conf, &counter, 1)), expected);
+    }
+
+    #[test]
+    fn test_rules_from_itemset() {
+        let counter: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![1] => 6,
+                itemset![2] => 5,
+                itemset![3] => 4,
+            },
+            2 => hashmap! {
+                itemset![1, 2] => 4,
+                itemset![1, 3] => 3,
+                itemset![2, 3] => 3,
+            },
+            3 => hashmap! {
+                itemset![1, 2, 3] => 2,
+            },
+        };
+
+        let rules = rules_from_itemset(&[1, 2, 3], 2, &counter, 0.0, 10);
+        assert_eq!(rules.len(), 6);
+
+        let rule = rules
+            .iter()
+            .find(|rule| rule.get_antecedent() == [1, 2])
+            .unwrap();
+        assert_eq!(rule.get_consequent(), [3]);
+        assert!((rule.confidence - 0.5).abs() < 1e-6);
+        assert!((rule.lift - 1.25).abs() < 1e-6);
+        assert_eq!(rule.antecedent_support, SupportCount(4));
+        assert_eq!(rule.union_support, SupportCount(2));
+
+        let rule = rules
+            .iter()
+            .find(|rule| rule.get_antecedent() == [3])
+            .unwrap();
+        assert_eq!(rule.antecedent_support, SupportCount(4));
+        assert_eq!(rule.union_support, SupportCount(2));
+
+        let confident_rules = rules_from_itemset(&[1, 2, 3], 2, &counter, 0.6, 10);
+        assert_eq!(confident_rules.len(), 2);
+    }
+
+    #[test]
+    fn test_generate_rules_from_closed() {
+        use crate::itemsets::{
+            count::try_generate_frequent_itemsets, options::MiningOptions, support::SupportFraction,
+        };
+        use crate::types::RawTransaction;
+
+        // cheese only ever comes with bread and milk
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["bread", "milk", "cheese"],
+            vec!["bread", "milk", "cheese"],
+            vec!["bread", "milk"],
+            vec!["bread", "jam"],
+            vec!["milk", "jam"],
+            vec!["bread", "milk", "cheese", "jam"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+        let (counter, _, _) = try_generate_frequent_itemsets(
+            raw_transactions,
+            SupportFraction(0.3),
+            None,
+            &MiningOptions::default(),
+        )
+        .unwrap();
+
+        let all_rules = generate_rules(&0.5, &counter, 6);
+        let closed_rules = generate_rules_from_closed(&0.5, &counter, 6);
+
+        assert!(!closed_rules.is_empty());
+        assert!(closed_rules.len() < all_rules.len());
+        for rule in &closed_rules {
+            assert!(all_rules.iter().any(|other| {
+                other.get_antecedent() == rule.get_antecedent()
+                    && other.get_consequent() == rule.get_consequent()
+                    && other.confidence == rule.confidence
+            }));
         }
+
+        let limited_rules = generate_rules_from_closed_limited(&0.5, &counter, 6, 1);
+        assert!(!limited_rules.is_empty());
+        assert!(limited_rules.len() < closed_rules.len());
+        assert!(limited_rules
+            .iter()
+            .all(|rule| rule.get_consequent().len() == 1));
     }
 }
@@ -0,0 +1,114 @@
+use crate::{rules::rule::Rule, types::ItemId};
+
+/// Smallest, median and largest of a set of values.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Spread {
+    pub min: f32,
+    /// Middle value, or the mean of the two middle values for an even count.
+    pub median: f32,
+    pub max: f32,
+}
+
+/// Profile of a rule set. See [`summarize_rules`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RuleSummary {
+    pub num_rules: usize,
+    pub confidence: Spread,
+    pub lift: Spread,
+    /// Mean number of items on the left-hand side.
+    pub mean_antecedent_len: f32,
+    /// Mean number of items on the right-hand side.
+    pub mean_consequent_len: f32,
+}
+
+/// Count the rules and profile their confidence, lift and side lengths, to
+/// check at a glance whether the thresholds gave a sensible rule set.
+/// Every field is 0 if there are no rules.
+pub fn summarize_rules(rules: &[Rule]) -> RuleSummary {
+    if rules.is_empty() {
+        return RuleSummary::default();
+    }
+    let num_rules = rules.len() as f32;
+    let mean_len = |side: fn(&Rule) -> &[ItemId]| {
+        rules.iter().map(|rule| side(rule).len()).sum::<usize>() as f32 / num_rules
+    };
+
+    RuleSummary {
+        num_rules: rules.len(),
+        confidence: spread(rules.iter().map(|rule| rule.confidence).collect()),
+        lift: spread(rules.iter().map(|rule| rule.lift).collect()),
+        mean_antecedent_len: mean_len(Rule::get_antecedent),
+        mean_consequent_len: mean_len(Rule::get_consequent),
+    }
+}
+
+/// `values` must not be empty.
+fn spread(mut values: Vec<f32>) -> Spread {
+    values.sort_unstable_by(f32::total_cmp);
+    let middle = values.len() / 2;
+    let median = if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    };
+
+    Spread {
+        min: values[0],
+        median,
+        max: values[values.len() - 1],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::support::SupportCount;
+
+    fn rule(combi: Vec<usize>, split: usize, confidence: f32, lift: f32) -> Rule {
+        Rule {
+            split,
+            combi,
+            confidence,
+            lift,
+            antecedent_support: SupportCount::default(),
+            union_support: SupportCount::default(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_rules() {
+        let rules = vec![
+            rule(vec![0, 1], 1, 0.5, 1.2),
+            rule(vec![0, 1, 2], 2, 0.9, 2.0),
+            rule(vec![0, 1, 2], 1, 0.6, 0.8),
+            rule(vec![1, 2], 1, 0.8, 1.5),
+        ];
+
+        let summary = summarize_rules(&rules);
+
+        assert_eq!(summary.num_rules, 4);
+        // even count: mean of 0.6 and 0.8
+        assert_eq!(summary.confidence.min, 0.5);
+        assert!((summary.confidence.median - 0.7).abs() < 1e-6);
+        assert_eq!(summary.confidence.max, 0.9);
+        assert_eq!((summary.lift.min, summary.lift.max), (0.8, 2.0));
+        assert!((summary.lift.median - 1.35).abs() < 1e-6);
+        assert_eq!(summary.mean_antecedent_len, 1.25);
+        assert_eq!(summary.mean_consequent_len, 1.25);
+    }
+
+    #[test]
+    fn test_summarize_rules_odd_and_empty() {
+        let rules = vec![
+            rule(vec![0, 1], 1, 0.3, 1.0),
+            rule(vec![0, 1], 1, 0.9, 3.0),
+            rule(vec![0, 1], 1, 0.4, 2.0),
+        ];
+
+        let summary = summarize_rules(&rules);
+
+        assert_eq!(summary.confidence.median, 0.4);
+        assert_eq!(summary.lift.median, 2.0);
+        assert_eq!(summarize_rules(&[]), RuleSummary::default());
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    rules::rule::Rule,
+    types::{Inventory, ItemName, RawTransaction},
+};
+
+/// Recompute the confidence of each rule on a holdout set of transactions.
+///
+/// Every rule is checked by scanning `holdout` directly, matching items by the
+/// names in `inventory`. Returns `(index into rules, holdout confidence)`
+/// pairs; rules whose antecedent never occurs in the holdout have no defined
+/// confidence and are left out. A large drop from the mined confidence flags a
+/// rule that does not generalise.
+pub fn validate_rules(
+    rules: &[Rule],
+    holdout: &[RawTransaction],
+    inventory: &Inventory,
+) -> Vec<(usize, f32)> {
+    rules
+        .iter()
+        .enumerate()
+        .filter_map(|(index, rule)| {
+            let names = |items: &[usize]| -> Vec<ItemName> {
+                items.iter().map(|item| inventory[item]).collect()
+            };
+            let antecedent = names(rule.get_antecedent());
+            let consequent = names(rule.get_consequent());
+
+            let mut antecedent_count = 0_u32;
+            let mut union_count = 0_u32;
+            for transaction in holdout {
+                if antecedent.iter().all(|item| transaction.contains(item)) {
+                    antecedent_count += 1;
+                    if consequent.iter().all(|item| transaction.contains(item)) {
+                        union_count += 1;
+                    }
+                }
+            }
+
+            if antecedent_count == 0 {
+                return None;
+            }
+            Some((index, union_count as f32 / antecedent_count as f32))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::itemsets::support::SupportFraction;
+    use crate::{itemsets::count::generate_frequent_itemsets, rules::search::generate_rules};
+
+    fn raw_transactions(transactions: Vec<Vec<&str>>) -> Vec<RawTransaction<'_>> {
+        transactions
+            .into_iter()
+            .map(|items| items.into_iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_rules() {
+        let training = raw_transactions(vec![
+            vec!["bread", "milk"],
+            vec!["bread", "milk"],
+            vec!["bread", "milk", "eggs"],
+            vec!["milk"],
+        ]);
+        let holdout = raw_transactions(vec![
+            vec!["bread", "milk"],
+            vec!["bread"],
+            vec!["bread", "eggs"],
+            vec!["bread"],
+            vec!["eggs"],
+        ]);
+        let N = training.len();
+        let (itemsets, inventory) = generate_frequent_itemsets(training, SupportFraction(0.5), 2);
+        let rules = generate_rules(&0.0, &itemsets, N);
+
+        let validated = validate_rules(&rules, &holdout, &inventory);
+
+        let position = |antecedent: &str, consequent: &str| {
+            rules
+                .iter()
+                .position(|rule| {
+                    inventory[&rule.get_antecedent()[0]] == antecedent
+                        && inventory[&rule.get_consequent()[0]] == consequent
+                })
+                .unwrap()
+        };
+
+        // bread -> milk always held in training, but only once in 4 holdout baskets
+        let bread_to_milk = position("bread", "milk");
+        assert!((rules[bread_to_milk].confidence - 1.0).abs() < 1e-6);
+        assert!(validated.contains(&(bread_to_milk, 0.25)));
+
+        // milk -> bread still holds on the single holdout basket with milk
+        let milk_to_bread = position("milk", "bread");
+        assert!((rules[milk_to_bread].confidence - 0.75).abs() < 1e-6);
+        assert!(validated.contains(&(milk_to_bread, 1.0)));
+    }
+
+    #[test]
+    fn test_validate_rules_skips_unseen_antecedents() {
+        let training = raw_transactions(vec![vec!["tea", "milk"], vec!["tea", "milk"]]);
+        let holdout = raw_transactions(vec![vec!["coffee"]]);
+        let (itemsets, inventory) = generate_frequent_itemsets(training, SupportFraction(0.5), 2);
+        let rules = generate_rules(&0.0, &itemsets, 2);
+
+        assert_eq!(rules.len(), 2);
+        assert!(validate_rules(&rules, &holdout, &inventory).is_empty());
+    }
+}
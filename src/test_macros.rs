@@ -0,0 +1,24 @@
+//! A `maplit`-style literal for the crate's own map types.
+//!
+//! `maplit` always builds std's `RandomState` maps, which stop matching the
+//! crate's aliases once `deterministic-hash` swaps the hasher. This infers the
+//! hasher from where the literal is used instead.
+
+macro_rules! hashmap {
+    ($($key:expr => $value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut map = ::std::collections::HashMap::with_hasher(Default::default());
+        $(map.insert($key, $value);)*
+        map
+    }};
+}
+
+
+/// An [`Itemset`](crate::types::Itemset) literal, like `vec!` but building
+/// whichever representation the `smallvec` feature selects.
+macro_rules! itemset {
+    ($($item:expr),* $(,)?) => {{
+        let items: &[$crate::types::ItemId] = &[$($item),*];
+        $crate::types::Itemset::from(items)
+    }};
+}
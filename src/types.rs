@@ -1,22 +1,44 @@
+#[cfg(feature = "python")]
 use pyo3::{Py, types::PyDict};
 
 use crate::{HashMap,HashSet};
 
+/// Hasher behind the crate's maps and sets.
+///
+/// Defaults to std's randomly seeded `RandomState`, which resists hash
+/// flooding. The `fast-hash` feature swaps in `ahash`, which is much quicker
+/// on the small integer and itemset keys used here. The `deterministic-hash`
+/// feature swaps in a fixed-key `AHasher` so that repeated runs over the same
+/// input lay out, and iterate, their maps identically; it takes precedence
+/// over `fast-hash`.
+#[cfg(not(any(feature = "fast-hash", feature = "deterministic-hash")))]
+pub type HashState = std::collections::hash_map::RandomState;
+#[cfg(all(feature = "fast-hash", not(feature = "deterministic-hash")))]
+pub type HashState = ahash::RandomState;
+#[cfg(feature = "deterministic-hash")]
+pub type HashState = std::hash::BuildHasherDefault<ahash::AHasher>;
+
 pub type ItemId = usize;
 pub type ItemName<'l> = &'l str;
 pub type PyItemName = String;
+#[cfg(not(feature = "smallvec"))]
 pub type Itemset = Vec<ItemId>;
+#[cfg(feature = "smallvec")]
+pub type Itemset = smallvec::SmallVec<[ItemId; 4]>;
 
-pub type ReverseLookup<'l> = HashMap<ItemName<'l>, ItemId>;
-pub type Inventory<'l> = HashMap<ItemId, ItemName<'l>>;
+pub type ReverseLookup<'l> = HashMap<ItemName<'l>, ItemId, HashState>;
+pub type Inventory<'l> = HashMap<ItemId, ItemName<'l>, HashState>;
+pub type RemapTable = HashMap<ItemId, ItemId, HashState>;
 
-pub type RawTransaction<'l> = HashSet<ItemName<'l>>;
-pub type RawTransactionId = HashSet<ItemId>;
+pub type RawTransaction<'l> = HashSet<ItemName<'l>, HashState>;
+pub type RawTransactionId = HashSet<ItemId, HashState>;
 pub type Transaction = Vec<ItemId>;
 
-pub type ItemCounts = HashMap<ItemId, u32>;
-pub type ItemsetCounts = HashMap<Itemset, u32>;
+pub type ItemCounts = HashMap<ItemId, u32, HashState>;
+pub type ItemsetCounts = HashMap<Itemset, u32, HashState>;
+pub type PairCounts = HashMap<(ItemId, ItemId), u32, HashState>;
 
 pub type ItemsetLength = usize;
-pub type FrequentItemsets = HashMap<ItemsetLength, ItemsetCounts>;
+pub type FrequentItemsets = HashMap<ItemsetLength, ItemsetCounts, HashState>;
+#[cfg(feature = "python")]
 pub type PyFrequentItemsets = Py<PyDict>;
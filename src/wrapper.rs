@@ -64,6 +64,8 @@ pub fn convert_rules(rules: Vec<rules::rule::Rule>, inventory: Inventory) -> Vec
                 .collect(),
             confidence: x.confidence,
             lift: x.lift,
+            antecedent_support: x.antecedent_support.0,
+            union_support: x.union_support.0,
         })
         .collect();
     pyrules.sort_by(|a, b| (-a.confidence).partial_cmp(&-b.confidence).unwrap_or(Equal));
@@ -0,0 +1,367 @@
+use std::io::{self, Write};
+
+#[cfg(feature = "serde")]
+use serde_json::{json, Value};
+
+#[cfg(feature = "serde")]
+use crate::result::MiningResult;
+use crate::{
+    itemsets::{count::levels_iter, support::SupportFraction},
+    reader::csv_delimiter,
+    types::{FrequentItemsets, Inventory, ItemName, ItemsetCounts, ItemsetLength, RawTransaction},
+};
+
+/// Write frequent itemsets in the FIMI output format: one itemset per line,
+/// its item labels separated by spaces, followed by the support count in
+/// parentheses, e.g. `bread milk (3)`.
+///
+/// Lines are ordered by itemset length, then by label, and labels within a
+/// line are sorted, so the output can be diffed against other implementations.
+pub fn write_fimi<W: Write>(
+    itemsets: &FrequentItemsets,
+    inventory: &Inventory,
+    mut writer: W,
+) -> io::Result<()> {
+    let mut sizes: Vec<ItemsetLength> = itemsets.keys().copied().collect();
+    sizes.sort_unstable();
+
+    for size in sizes {
+        write_fimi_level(&itemsets[&size], inventory, &mut writer)?;
+    }
+    writer.flush()
+}
+
+/// Write a single level in the format of [`write_fimi`], sorted by label.
+fn write_fimi_level<W: Write>(
+    itemset_counts: &ItemsetCounts,
+    inventory: &Inventory,
+    writer: &mut W,
+) -> io::Result<()> {
+    for (labels, count) in labelled_level(itemset_counts, inventory) {
+        writeln!(writer, "{} ({})", labels.join(" "), count)?;
+    }
+    Ok(())
+}
+
+/// The itemsets of one level as sorted labels with their counts, sorted.
+fn labelled_level<'l>(
+    itemset_counts: &ItemsetCounts,
+    inventory: &Inventory<'l>,
+) -> Vec<(Vec<ItemName<'l>>, u32)> {
+    let mut lines: Vec<(Vec<ItemName>, u32)> = itemset_counts
+        .iter()
+        .map(|(itemset, &count)| {
+            let mut labels: Vec<ItemName> =
+                itemset.iter().map(|item_id| inventory[item_id]).collect();
+            labels.sort_unstable();
+            (labels, count)
+        })
+        .collect();
+    lines.sort_unstable();
+    lines
+}
+
+/// Write frequent itemsets as CSV: one row per itemset, its support count
+/// followed by its item names, with fields separated by `delimiter`. Rows are
+/// ordered as in [`write_fimi`].
+///
+/// Names containing the delimiter, quotes or line breaks are quoted, with
+/// quotes doubled, so any CSV reader gets them back unchanged.
+///
+/// # Panics
+///
+/// If `delimiter` is not an ASCII character.
+pub fn write_itemsets_csv<W: Write>(
+    itemsets: &FrequentItemsets,
+    inventory: &Inventory,
+    delimiter: char,
+    writer: W,
+) -> io::Result<()> {
+    let mut sizes: Vec<ItemsetLength> = itemsets.keys().copied().collect();
+    sizes.sort_unstable();
+
+    let mut writer = csv_writer(delimiter, writer);
+    for size in sizes {
+        for (labels, count) in labelled_level(&itemsets[&size], inventory) {
+            writer.write_field(count.to_string())?;
+            writer.write_record(labels)?;
+        }
+    }
+    writer.flush()
+}
+
+/// Write transactions as a basket file, one per line with its items sorted
+/// and separated by `delimiter`, quoted as in [`write_itemsets_csv`].
+/// [`parse_transaction_with_delimiter`](crate::reader::parse_transaction_with_delimiter)
+/// reads every line back into the same transaction, as long as no item
+/// contains a line break or starts or ends with whitespace, which the reader
+/// trims.
+///
+/// # Panics
+///
+/// If `delimiter` is not an ASCII character.
+pub fn write_transactions_csv<W: Write>(
+    raw_transactions: &[RawTransaction],
+    delimiter: char,
+    writer: W,
+) -> io::Result<()> {
+    let mut writer = csv_writer(delimiter, writer);
+    for transaction in raw_transactions {
+        let mut items: Vec<ItemName> = transaction.iter().copied().collect();
+        items.sort_unstable();
+        writer.write_record(items)?;
+    }
+    writer.flush()
+}
+
+/// CSV writer with `delimiter` between fields, rows of any length and `\n`
+/// line endings.
+fn csv_writer<W: Write>(delimiter: char, writer: W) -> csv::Writer<W> {
+    csv::WriterBuilder::new()
+        .delimiter(csv_delimiter(delimiter))
+        .has_headers(false)
+        .flexible(true)
+        .terminator(csv::Terminator::Any(b'\n'))
+        .from_writer(writer)
+}
+
+/// Mine frequent itemsets of up to `k` items and write them to `writer` in the
+/// format of [`write_fimi`], without ever collecting a [`FrequentItemsets`].
+///
+/// Each level is written out and dropped as soon as the next level's
+/// candidates have been generated from it, so at most one level of results is
+/// held in memory. The output is identical to mining with
+/// [`generate_frequent_itemsets`](crate::itemsets::count::generate_frequent_itemsets)
+/// and passing the result to [`write_fimi`].
+pub fn mine_to_writer<W: Write>(
+    raw_transactions: Vec<RawTransaction>,
+    min_support: SupportFraction,
+    k: ItemsetLength,
+    mut writer: W,
+) -> io::Result<()> {
+    let mut levels = levels_iter(raw_transactions, min_support, k);
+    while let Some((_, itemset_counts)) = levels.next() {
+        write_fimi_level(&itemset_counts, levels.inventory(), &mut writer)?;
+    }
+
+    writer.flush()
+}
+
+/// Frequent itemsets as one JSON document for front-ends: an array of levels
+/// in ascending size, each like `{ "size": 2, "itemsets": [...] }` with
+/// entries like `{ "items": ["bread", "milk"], "count": 4, "support": 0.5 }`,
+/// names resolved through the inventory and `support` the fraction of N.
+///
+/// Entries are ordered as the lines of [`write_fimi`]. Empty levels are kept
+/// with an empty `itemsets` array, and since every level carries its size, a
+/// level missing from the result does not shift the ones after it.
+#[cfg(feature = "serde")]
+pub fn to_json(result: &MiningResult) -> Value {
+    let mut sizes: Vec<ItemsetLength> = result.itemsets.keys().copied().collect();
+    sizes.sort_unstable();
+
+    sizes
+        .into_iter()
+        .map(|size| {
+            let itemsets = labelled_level(&result.itemsets[&size], &result.inventory)
+                .into_iter()
+                .map(|(labels, count)| {
+                    json!({
+                        "items": labels,
+                        "count": count,
+                        "support": f64::from(count) / result.N as f64,
+                    })
+                })
+                .collect::<Value>();
+            json!({ "size": size, "itemsets": itemsets })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_fimi() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 5,
+                itemset![1] => 6,
+                itemset![2] => 4,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 5,
+                itemset![1, 2] => 4,
+            },
+            3 => hashmap! {},
+        };
+        let inventory: Inventory = hashmap! { 0 => "bread", 1 => "milk", 2 => "cheese" };
+
+        let mut output = Vec::new();
+        write_fimi(&itemsets, &inventory, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "bread (5)\ncheese (4)\nmilk (6)\nbread milk (5)\ncheese milk (4)\n"
+        );
+    }
+
+    #[test]
+    fn test_write_itemsets_csv() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 5,
+                itemset![1] => 3,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 3,
+            },
+        };
+        let inventory: Inventory = hashmap! { 0 => "Widget, Large", 1 => r#"12" pipe"# };
+
+        let mut output = Vec::new();
+        write_itemsets_csv(&itemsets, &inventory, ',', &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#"3,"12"" pipe"
+5,"Widget, Large"
+3,"12"" pipe","Widget, Large"
+"#
+        );
+    }
+
+    #[test]
+    fn test_transactions_csv_round_trip() {
+        use crate::reader::parse_transaction_with_delimiter;
+
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["Widget, Large", "bolt"],
+            vec![r#"12" pipe"#, "pad ded", "a;b"],
+            vec![r#""quoted""#],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        for &delimiter in &[',', ';', '\t'] {
+            let mut output = Vec::new();
+            write_transactions_csv(&raw_transactions, delimiter, &mut output).unwrap();
+
+            let read_back: Vec<Vec<String>> = String::from_utf8(output)
+                .unwrap()
+                .lines()
+                .map(|line| {
+                    let mut items: Vec<String> = parse_transaction_with_delimiter(line, delimiter)
+                        .into_iter()
+                        .collect();
+                    items.sort_unstable();
+                    items
+                })
+                .collect();
+            let expected: Vec<Vec<String>> = raw_transactions
+                .iter()
+                .map(|transaction| {
+                    let mut items: Vec<String> =
+                        transaction.iter().map(|item| item.to_string()).collect();
+                    items.sort_unstable();
+                    items
+                })
+                .collect();
+
+            assert_eq!(read_back, expected);
+        }
+    }
+
+    #[test]
+    fn test_mine_to_writer_matches_full_mine() {
+        use crate::itemsets::count::generate_frequent_itemsets;
+
+        let raw_transactions: Vec<RawTransaction> = vec![
+            vec!["bread", "milk", "cheese"],
+            vec!["bread", "milk"],
+            vec!["milk", "cheese", "bread"],
+            vec!["milk", "cheese", "yoghurt"],
+            vec!["bread", "cheese", "yoghurt"],
+            vec!["milk", "bread", "yoghurt", "cheese"],
+        ]
+        .into_iter()
+        .map(|items| items.into_iter().collect())
+        .collect();
+
+        for &(min_support, k) in &[(0.3, 1), (0.3, 2), (0.3, 4), (0.5, 3), (0.9, 3)] {
+            let (itemsets, inventory) = generate_frequent_itemsets(
+                raw_transactions.clone(),
+                SupportFraction(min_support),
+                k,
+            );
+            let mut expected = Vec::new();
+            write_fimi(&itemsets, &inventory, &mut expected).unwrap();
+
+            let mut output = Vec::new();
+            mine_to_writer(
+                raw_transactions.clone(),
+                SupportFraction(min_support),
+                k,
+                &mut output,
+            )
+            .unwrap();
+
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                String::from_utf8(expected).unwrap()
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_to_json() {
+        let itemsets: FrequentItemsets = hashmap! {
+            1 => hashmap! {
+                itemset![0] => 3,
+                itemset![1] => 4,
+                itemset![2] => 2,
+            },
+            2 => hashmap! {
+                itemset![0, 1] => 2,
+            },
+            3 => hashmap! {},
+        };
+        let inventory: Inventory = hashmap! { 0 => "bread", 1 => "milk", 2 => "cheese" };
+
+        let json = to_json(&MiningResult::new(itemsets.clone(), inventory.clone(), 4));
+
+        assert_eq!(
+            json,
+            json!([
+                {
+                    "size": 1,
+                    "itemsets": [
+                        { "items": ["bread"], "count": 3, "support": 0.75 },
+                        { "items": ["cheese"], "count": 2, "support": 0.5 },
+                        { "items": ["milk"], "count": 4, "support": 1.0 },
+                    ],
+                },
+                {
+                    "size": 2,
+                    "itemsets": [{ "items": ["bread", "milk"], "count": 2, "support": 0.5 }],
+                },
+                { "size": 3, "itemsets": [] },
+            ])
+        );
+
+        // without level 2, level 3 still says which size it is
+        let mut itemsets = itemsets;
+        itemsets.remove(&2);
+        let json = to_json(&MiningResult::new(itemsets, inventory, 4));
+        let sizes: Vec<&Value> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|level| &level["size"])
+            .collect();
+        assert_eq!(sizes, [1, 3]);
+    }
+}